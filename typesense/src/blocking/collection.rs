@@ -0,0 +1,137 @@
+use std::io::{self, Read};
+use std::sync::Arc;
+
+use bytes::{Buf, Bytes};
+use futures_util::StreamExt;
+use tokio::runtime::Runtime;
+use typesense_codegen::models::{SearchParameters, SearchResult};
+
+use super::Client;
+use crate::collection::{CollectionResponse, CollectionSchema, ImportAction};
+use crate::document::Document;
+use crate::transport::{BodyStream, HttpLowLevel, HttpLowLevelStream};
+use crate::Result;
+
+/// Size of the chunks the documents to import are read in.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Blocking client for the Typesense CollectionAPI
+pub struct CollectionClient<T> {
+    pub(super) client: Client<T>,
+}
+
+impl<T> CollectionClient<T>
+where
+    T: HttpLowLevel + Clone,
+{
+    /// Create a collection in Typesense for a [`Document`] type.
+    pub fn create<D: Document>(&self) -> Result<CollectionResponse> {
+        self.client.block_on(self.inner().create::<D>())
+    }
+
+    /// Create a Collection in Typesense given a ['CollectionSchema`]
+    pub fn create_from_schema(&self, schema: CollectionSchema) -> Result<CollectionResponse> {
+        self.client
+            .block_on(self.inner().create_from_schema(schema))
+    }
+
+    /// Retrieve the details of the collection given a collection name
+    pub fn retrieve(&self, collection_name: &str) -> Result<CollectionResponse> {
+        self.client.block_on(self.inner().retrieve(collection_name))
+    }
+
+    /// Retrieve all the collections
+    pub fn retrieve_all(&self) -> Result<Vec<CollectionResponse>> {
+        self.client.block_on(self.inner().retrieve_all())
+    }
+
+    /// Import documents, formatted as JSON lines, into a collection.
+    ///
+    /// The documents are sent as they are read from `documents`, so they
+    /// can be read from a file without holding them all in memory.
+    /// See [`crate::collection::CollectionClient::import_documents`].
+    pub fn import_documents(
+        &self,
+        collection_name: &str,
+        documents: impl Read + Send + 'static,
+        action: impl Into<Option<ImportAction>>,
+    ) -> Result<String> {
+        let chunks = futures_util::stream::try_unfold(documents, |mut documents| async move {
+            let mut chunk = vec![0; CHUNK_SIZE];
+            let len = documents.read(&mut chunk)?;
+            chunk.truncate(len);
+
+            Ok((len > 0).then(|| (Bytes::from(chunk), documents)))
+        });
+
+        self.client.block_on(self.inner().import_documents(
+            collection_name,
+            Box::pin(chunks),
+            action,
+        ))
+    }
+
+    /// Search for documents in a collection.
+    /// See [`crate::collection::CollectionClient::search`].
+    pub fn search(
+        &self,
+        collection_name: &str,
+        parameters: &SearchParameters,
+    ) -> Result<SearchResult> {
+        self.client
+            .block_on(self.inner().search(collection_name, parameters))
+    }
+
+    /// Permanently drops a collection. This action cannot be undone.
+    /// For large collections, this might have an impact on read latencies.
+    pub fn delete(&self, collection_name: &str) -> Result<CollectionResponse> {
+        self.client.block_on(self.inner().delete(collection_name))
+    }
+
+    fn inner(&self) -> crate::collection::CollectionClient<T> {
+        self.client.inner.collection()
+    }
+}
+
+impl<T> CollectionClient<T>
+where
+    T: HttpLowLevelStream + Clone,
+{
+    /// Export all the documents of a collection, as JSON lines.
+    ///
+    /// The documents are read as they are received, so even
+    /// large collections can be exported without buffering them.
+    pub fn export_documents(&self, collection_name: &str) -> Result<BodyReader> {
+        let body = self
+            .client
+            .block_on(self.inner().export_documents(collection_name))?;
+
+        Ok(BodyReader {
+            body,
+            chunk: Bytes::new(),
+            runtime: self.client.runtime.clone(),
+        })
+    }
+}
+
+/// A body received chunk by chunk, read while it is received.
+pub struct BodyReader {
+    body: BodyStream,
+    chunk: Bytes,
+    runtime: Arc<Runtime>,
+}
+
+impl Read for BodyReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.chunk.has_remaining() {
+            match self.runtime.block_on(self.body.next()) {
+                Some(chunk) => self.chunk = chunk.map_err(io::Error::other)?,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.remaining());
+        self.chunk.copy_to_slice(&mut buf[..len]);
+        Ok(len)
+    }
+}
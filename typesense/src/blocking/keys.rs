@@ -0,0 +1,71 @@
+use futures_util::FutureExt;
+use typesense_codegen::models::{ApiKey, ApiKeysResponse};
+
+use super::Client;
+use crate::keys::{Actions, ClientKeyDelete};
+use crate::transport::HttpLowLevel;
+
+/// To interact with the Keys API, blocking.
+pub struct ClientKeys<T> {
+    pub(super) client: Client<T>,
+}
+
+impl<T> ClientKeys<T>
+where
+    T: HttpLowLevel + Clone,
+{
+    /// Create an API Key.
+    ///
+    /// More info [here](https://typesense.org/docs/0.20.0/api/api-keys.html#create-an-api-key).
+    pub fn create(
+        &self,
+        actions: Vec<Actions>,
+        collections: Vec<String>,
+        description: impl Into<Option<String>>,
+        expires_at: impl Into<Option<i64>>,
+    ) -> crate::Result<ApiKey> {
+        self.client.block_on(
+            self.inner()
+                .create(actions, collections, description, expires_at),
+        )
+    }
+
+    /// Retrieve (metadata about) a key.
+    ///
+    /// More info [here](https://typesense.org/docs/0.20.0/api/api-keys.html#retrieve-an-api-key).
+    pub fn retrieve(&self, n: usize) -> crate::Result<ApiKey> {
+        self.client.block_on(self.inner().retrieve(n))
+    }
+
+    /// Retrieve (metadata about) all keys.
+    ///
+    /// More info [here](https://typesense.org/docs/0.20.0/api/api-keys.html#list-all-keys).
+    pub fn retrieve_all(&self) -> crate::Result<ApiKeysResponse> {
+        self.client.block_on(self.inner().retrieve_all())
+    }
+
+    /// Delete an API key given its ID.
+    ///
+    /// More info [here](https://typesense.org/docs/0.20.0/api/api-keys.html#delete-api-key).
+    pub fn delete(&self, n: usize) -> crate::Result<ClientKeyDelete> {
+        self.client.block_on(self.inner().delete(n))
+    }
+
+    /// Generate a scoped search API key that can have embedded search parameters in them.
+    ///
+    /// More info [here](https://typesense.org/docs/0.20.0/api/api-keys.html#generate-scoped-search-key).
+    pub fn generate_scoped_search_key(
+        key: impl AsRef<str>,
+        filter_by: impl AsRef<str>,
+        expires_at: usize,
+    ) -> crate::Result<String> {
+        // The key is generated locally, the future is ready right away.
+        crate::keys::ClientKeys::<T>::generate_scoped_search_key(key, filter_by, expires_at)
+            .now_or_never()
+            .expect("scoped search keys are generated without waiting")
+    }
+
+    fn inner(&self) -> crate::keys::ClientKeys<T> {
+        self.client.inner.keys()
+    }
+}
@@ -0,0 +1,339 @@
+//! A blocking Client API.
+//!
+//! The blocking [`Client`] offers the same API as the asynchronous
+//! [`Client`](crate::Client), but blocks the current thread until every
+//! request is done, driving it on an internal runtime. It is convenient for
+//! applications and scripts that only make a few requests.
+//!
+//! It must not be used from within an async runtime, as blocking
+//! there would prevent the runtime from making progress.
+//!
+//! ```no_run
+//! # fn main() -> typesense::Result<()> {
+//! let client = typesense::blocking::ClientBuilder::new_hyper()
+//!     .host("http://localhost:8108")
+//!     .api_key("VerySecretKey")
+//!     .build()?;
+//!
+//! let collections = client.collection().retrieve_all()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::runtime::Runtime;
+
+use crate::{NodeHealth, Result};
+
+mod collection;
+mod keys;
+
+pub use collection::{BodyReader, CollectionClient};
+pub use keys::ClientKeys;
+
+/// A blocking client for the Typesense APIs.
+///
+/// The client shares its runtime with its clones, which are cheap.
+#[derive(Clone)]
+pub struct Client<T> {
+    inner: crate::Client<T>,
+    runtime: Arc<Runtime>,
+}
+
+impl<T> Client<T> {
+    /// Gets the current health of the nodes of the client,
+    /// starting with the nearest node, if any.
+    pub fn nodes_health(&self) -> Vec<NodeHealth> {
+        self.inner.nodes_health()
+    }
+
+    /// Drop the cached results of the searches in a collection.
+    /// See [`crate::Client::invalidate_search_cache`].
+    pub fn invalidate_search_cache(&self, collection_name: &str) {
+        self.inner.invalidate_search_cache(collection_name)
+    }
+
+    /// Gets the asynchronous client wrapped by this client.
+    pub fn inner(&self) -> &crate::Client<T> {
+        &self.inner
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+impl<T> Client<T>
+where
+    T: Clone,
+{
+    /// Make a copy of the client whose requests use `timeout`
+    /// instead of the timeout of the transport.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            inner: self.inner.with_timeout(timeout),
+            runtime: self.runtime.clone(),
+        }
+    }
+
+    /// Make the ClientKeys struct, to interact with the Keys API.
+    pub fn keys(&self) -> ClientKeys<T> {
+        ClientKeys {
+            client: self.clone(),
+        }
+    }
+
+    /// Creates a [`CollectionClient`] to interact with the Typesense Collection API
+    pub fn collection(&self) -> CollectionClient<T> {
+        CollectionClient {
+            client: self.clone(),
+        }
+    }
+}
+
+/// Builder for the blocking [`Client`].
+///
+/// It accepts the same configuration as the [`ClientBuilder`](crate::ClientBuilder).
+pub struct ClientBuilder<T> {
+    inner: crate::ClientBuilder<T>,
+}
+
+impl<T> ClientBuilder<T> {
+    /// Create a builder configured from the environment variables.
+    /// See [`crate::ClientBuilder::from_env`].
+    pub fn from_env() -> Result<Self> {
+        Ok(crate::ClientBuilder::from_env()?.into())
+    }
+
+    /// Apply a [`ClientConfig`](crate::ClientConfig), overriding the settings it holds.
+    pub fn config(self, config: crate::ClientConfig) -> Self {
+        self.map(|inner| inner.config(config))
+    }
+
+    /// build [`Client`] with the current configurations. Return [`crate::TypesenseError::ConfigError`]
+    /// if a configuration is missing.
+    pub fn build(self) -> Result<Client<T>> {
+        let inner = self.inner.build()?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+
+        Ok(Client {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// Set host, the single node of the cluster.
+    pub fn host(self, host: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.host(host))
+    }
+
+    /// Set the nodes of the cluster. See [`crate::ClientBuilder::nodes`].
+    pub fn nodes<I>(self, nodes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.map(|inner| inner.nodes(nodes))
+    }
+
+    /// Set the nearest node, which is tried before any of the other [`nodes`](Self::nodes).
+    pub fn nearest_node(self, nearest_node: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.nearest_node(nearest_node))
+    }
+
+    /// Set the interval after which a node that failed is probed again.
+    /// See [`crate::ClientBuilder::healthcheck_interval`].
+    pub fn healthcheck_interval(self, healthcheck_interval: Duration) -> Self {
+        self.map(|inner| inner.healthcheck_interval(healthcheck_interval))
+    }
+
+    /// Set api key
+    pub fn api_key(self, api_key: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.api_key(api_key))
+    }
+
+    /// Send the api key as a query parameter instead of a header.
+    /// See [`crate::ClientBuilder::api_key_in_query`].
+    pub fn api_key_in_query(self, api_key_in_query: bool) -> Self {
+        self.map(|inner| inner.api_key_in_query(api_key_in_query))
+    }
+
+    /// Add a header sent with every request.
+    /// See [`crate::ClientBuilder::default_header`].
+    pub fn default_header(self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.default_header(name, value))
+    }
+
+    /// Set the `User-Agent` header sent with every request.
+    /// See [`crate::ClientBuilder::user_agent`].
+    pub fn user_agent(self, user_agent: impl AsRef<str>) -> Self {
+        self.map(|inner| inner.user_agent(user_agent))
+    }
+
+    /// Set transport
+    pub fn transport(self, transport: crate::transport::Transport<T>) -> Self {
+        self.map(|inner| inner.transport(transport))
+    }
+
+    /// Set the timeout of the requests, instead of the timeout of the transport.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.map(|inner| inner.timeout(timeout))
+    }
+
+    /// Set the [`RetryPolicy`](crate::transport::RetryPolicy), instead of the one of the transport.
+    pub fn retry_policy(self, retry_policy: crate::transport::RetryPolicy) -> Self {
+        self.map(|inner| inner.retry_policy(retry_policy))
+    }
+
+    /// Set the [`MetricsRecorder`](crate::metrics::MetricsRecorder) told about every request of the client.
+    pub fn metrics_recorder(
+        self,
+        recorder: impl crate::metrics::MetricsRecorder + 'static,
+    ) -> Self {
+        self.map(|inner| inner.metrics_recorder(recorder))
+    }
+
+    /// Set the budget of the searches sent to each node.
+    /// See [`crate::ClientBuilder::search_limit`].
+    pub fn search_limit(self, limit: crate::RequestLimit) -> Self {
+        self.map(|inner| inner.search_limit(limit))
+    }
+
+    /// Set the budget of the writes sent to each node.
+    /// See [`crate::ClientBuilder::write_limit`].
+    pub fn write_limit(self, limit: crate::RequestLimit) -> Self {
+        self.map(|inner| inner.write_limit(limit))
+    }
+
+    /// Cache the results of up to `capacity` searches, for `ttl` each.
+    /// See [`crate::ClientBuilder::search_cache`].
+    pub fn search_cache(self, capacity: usize, ttl: Duration) -> Self {
+        self.map(|inner| inner.search_cache(capacity, ttl))
+    }
+
+    /// Coalesce the identical `GET` requests sent concurrently.
+    /// See [`crate::ClientBuilder::coalesce_requests`].
+    pub fn coalesce_requests(self, coalesce_requests: bool) -> Self {
+        self.map(|inner| inner.coalesce_requests(coalesce_requests))
+    }
+
+    /// Hedge the slow `GET` requests on another node.
+    /// See [`crate::ClientBuilder::hedge_requests`].
+    pub fn hedge_requests(self, hedge_policy: crate::HedgePolicy) -> Self {
+        self.map(|inner| inner.hedge_requests(hedge_policy))
+    }
+
+    fn map(self, f: impl FnOnce(crate::ClientBuilder<T>) -> crate::ClientBuilder<T>) -> Self {
+        Self {
+            inner: f(self.inner),
+        }
+    }
+}
+
+impl<T> Default for ClientBuilder<T> {
+    fn default() -> Self {
+        Self {
+            inner: crate::ClientBuilder::default(),
+        }
+    }
+}
+
+impl<T> From<crate::ClientBuilder<T>> for ClientBuilder<T> {
+    fn from(inner: crate::ClientBuilder<T>) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "tokio-rt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-rt")))]
+impl ClientBuilder<crate::transport::HyperHttpsClient> {
+    /// Create client builder with a [`hyper`](https://docs.rs/hyper) client.
+    /// The connector used is [`HttpsConnector`](hyper_tls::HttpsConnector).
+    pub fn new_hyper() -> Self {
+        crate::ClientBuilder::new_hyper().into()
+    }
+}
+
+#[cfg(feature = "rustls")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rustls")))]
+impl ClientBuilder<crate::transport::HyperRustlsClient> {
+    /// Create client builder with a [`hyper`](https://docs.rs/hyper) client
+    /// using [`rustls`](https://docs.rs/rustls), trusting the webpki roots.
+    pub fn new_hyper_rustls() -> Self {
+        crate::ClientBuilder::new_hyper_rustls().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use http::{Method, StatusCode};
+
+    use super::*;
+    use crate::transport::{MockTransport, TransportBuilder};
+    use crate::TypesenseError;
+
+    fn client(mock: &MockTransport) -> Client<MockTransport> {
+        ClientBuilder::default()
+            .transport(TransportBuilder::new(mock.clone()).build())
+            .host("http://localhost:8108")
+            .api_key("VerySecretKey")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn sends_requests_without_async_runtime() {
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::OK, r#"{"keys":[]}"#)
+            .respond(
+                Method::POST,
+                "/collections/companies/documents/import",
+                StatusCode::OK,
+                r#"{"success":true}"#,
+            )
+            .respond(
+                Method::GET,
+                "/collections/companies/documents/export",
+                StatusCode::OK,
+                r#"{"id":"1"}"#,
+            );
+        let client = client(&mock);
+
+        assert!(client.keys().retrieve_all().unwrap().keys.is_empty());
+        assert!(matches!(
+            client.keys().retrieve(1),
+            Err(TypesenseError::ObjectNotFound(_))
+        ));
+
+        let result = client
+            .collection()
+            .import_documents(
+                "companies",
+                &b"{\"id\":\"1\"}\n"[..],
+                crate::collection::ImportAction::Upsert,
+            )
+            .unwrap();
+        assert_eq!(result, r#"{"success":true}"#);
+        assert_eq!(mock.requests()[2].body, b"{\"id\":\"1\"}\n");
+        assert_eq!(
+            mock.requests()[2].uri,
+            "http://localhost:8108/collections/companies/documents/import?action=upsert"
+        );
+
+        let mut documents = String::new();
+        client
+            .collection()
+            .export_documents("companies")
+            .unwrap()
+            .read_to_string(&mut documents)
+            .unwrap();
+        assert_eq!(documents, r#"{"id":"1"}"#);
+    }
+}
@@ -1,7 +1,14 @@
 use std::sync::Arc;
+use std::time::Duration;
 
-use super::Client;
-use crate::transport::Transport;
+use super::cache::SearchCache;
+use super::hedge::{HedgePolicy, Hedging};
+use super::limit::Limits;
+use super::{
+    Client, ClientConfig, MetricsRecorder, Nodes, RequestLimit, TYPESENSE_API_KEY_HEADER_NAME,
+    TYPESENSE_API_KEY_QUERY_PARAM,
+};
+use crate::transport::{RetryPolicy, Transport};
 
 #[cfg(target_arch = "wasm32")]
 use crate::transport::WasmClient;
@@ -10,30 +17,178 @@ use crate::{Result, TypesenseError};
 /// Builder for the Typesense [`Client`]
 pub struct ClientBuilder<T> {
     transport: Option<Transport<T>>,
-    host: Option<Arc<String>>,
+    nodes: Vec<Arc<String>>,
+    nearest_node: Option<Arc<String>>,
+    healthcheck_interval: Duration,
     api_key: Option<Arc<String>>,
+    api_key_in_query: bool,
+    default_headers: Vec<(String, String)>,
+    user_agent: Option<String>,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    metrics: Option<Arc<dyn MetricsRecorder>>,
+    limits: Limits,
+    search_cache: Option<(usize, Duration)>,
+    coalesce_requests: bool,
+    hedge_policy: Option<HedgePolicy>,
+    /// A setting of the applied [`ClientConfig`] that is invalid,
+    /// reported when building the client.
+    config_error: Option<String>,
 }
 
 impl<T> ClientBuilder<T> {
+    /// Create a builder configured from the environment variables.
+    ///
+    /// See [`ClientConfig::from_env`] for the variables read. The
+    /// transport still has to be set with [`transport`](Self::transport).
+    pub fn from_env() -> Result<Self> {
+        Ok(Self::default().config(ClientConfig::from_env()?))
+    }
+
+    /// Apply a [`ClientConfig`], overriding the settings it holds.
+    pub fn config(mut self, config: ClientConfig) -> Self {
+        if !config.nodes.is_empty() {
+            self = self.nodes(config.nodes);
+        }
+        if let Some(nearest_node) = config.nearest_node {
+            self = self.nearest_node(nearest_node);
+        }
+        if let Some(api_key) = config.api_key {
+            self = self.api_key(api_key);
+        }
+        if let Some(timeout_ms) = config.timeout_ms {
+            self = self.timeout(Duration::from_millis(timeout_ms));
+        }
+        if let Some(healthcheck_interval_ms) = config.healthcheck_interval_ms {
+            self = self.healthcheck_interval(Duration::from_millis(healthcheck_interval_ms));
+        }
+        if let Some(retry) = config.retry {
+            if retry.max_attempts == Some(0) {
+                self.config_error = Some("retry max_attempts must be at least 1".to_string());
+            }
+            self = self.retry_policy(retry.policy());
+        }
+        self
+    }
+
     /// build [`Client`] with the current configurations. Return [`typesense::TypesenseError::ConfigError`]
-    /// if a configuration is missing.
+    /// if a configuration is missing or invalid.
     pub fn build(self) -> Result<Client<T>> {
+        if let Some(error) = self.config_error {
+            return Err(TypesenseError::ConfigError(error));
+        }
+        if self.timeout == Some(Duration::ZERO) {
+            return Err(TypesenseError::ConfigError(
+                "client timeout must not be zero".to_string(),
+            ));
+        }
+        if self.nodes.is_empty() && self.nearest_node.is_none() {
+            return Err(TypesenseError::ConfigError(
+                "missing client host".to_string(),
+            ));
+        }
+        for node in self.nearest_node.iter().chain(&self.nodes) {
+            validate_node(node)?;
+        }
+        self.limits.search.validate("search")?;
+        self.limits.write.validate("write")?;
+        if let Some((0, _)) = self.search_cache {
+            return Err(TypesenseError::ConfigError(
+                "search cache can't hold any search".to_string(),
+            ));
+        }
+
+        let api_key = self
+            .api_key
+            .ok_or_else(|| TypesenseError::ConfigError("missing client api key".to_string()))?;
+        let mut headers = http::HeaderMap::new();
+        for (name, value) in &self.default_headers {
+            headers.append(header_name(name)?, header_value(name, value)?);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            headers.insert(
+                http::header::USER_AGENT,
+                header_value("user agent", user_agent)?,
+            );
+        }
+        let api_key_param = if self.api_key_in_query {
+            let param = serde_urlencoded::to_string([(TYPESENSE_API_KEY_QUERY_PARAM, &*api_key)])
+                .map_err(|e| TypesenseError::ConfigError(e.to_string()))?;
+            Some(param)
+        } else {
+            let api_key = http::HeaderValue::from_str(&api_key).map_err(|_| {
+                TypesenseError::ConfigError(
+                    "client api key is not a valid header value".to_string(),
+                )
+            })?;
+            headers.insert(TYPESENSE_API_KEY_HEADER_NAME, api_key);
+            None
+        };
+        let mut transport = self
+            .transport
+            .ok_or_else(|| TypesenseError::ConfigError("missing client transport".to_string()))?;
+        if let Some(retry_policy) = self.retry_policy {
+            transport.set_retry_policy(retry_policy);
+        }
+
         Ok(Client {
-            transport: self.transport.ok_or_else(|| {
-                TypesenseError::ConfigError("missing client transport".to_string())
-            })?,
-            host: self
-                .host
-                .ok_or_else(|| TypesenseError::ConfigError("missing client host".to_string()))?,
-            api_key: self
-                .api_key
-                .ok_or_else(|| TypesenseError::ConfigError("missing client api key".to_string()))?,
+            transport,
+            nodes: Arc::new(Nodes::new(
+                self.nearest_node,
+                self.nodes,
+                self.healthcheck_interval,
+                &self.limits,
+            )),
+            headers,
+            api_key_param,
+            timeout: self.timeout,
+            metrics: self.metrics,
+            search_cache: self
+                .search_cache
+                .map(|(capacity, ttl)| Arc::new(SearchCache::new(capacity, ttl))),
+            coalescer: self.coalesce_requests.then(Arc::default),
+            hedging: self
+                .hedge_policy
+                .map(|policy| Arc::new(Hedging::new(policy))),
         })
     }
 
-    /// Set host
+    /// Set host, the single node of the cluster.
     pub fn host(mut self, host: impl AsRef<str>) -> Self {
-        self.host = Some(Arc::new(host.as_ref().to_string()));
+        self.nodes = vec![Arc::new(host.as_ref().to_string())];
+        self
+    }
+
+    /// Set the nodes of the cluster.
+    ///
+    /// Requests are sent to the nodes in a round-robin fashion. If a node
+    /// can't be reached or answers with a server error, the request
+    /// is retried on the next node. The requests that are not idempotent,
+    /// such as `POST` or `PATCH`, are only retried when the node could
+    /// not be connected to, as the node may have applied them.
+    pub fn nodes<I>(mut self, nodes: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.nodes = nodes
+            .into_iter()
+            .map(|node| Arc::new(node.as_ref().to_string()))
+            .collect();
+        self
+    }
+
+    /// Set the nearest node, which is tried before any of the other [`nodes`](Self::nodes).
+    pub fn nearest_node(mut self, nearest_node: impl AsRef<str>) -> Self {
+        self.nearest_node = Some(Arc::new(nearest_node.as_ref().to_string()));
+        self
+    }
+
+    /// Set the interval after which a node that failed is probed through
+    /// the `/health` endpoint, and used again if healthy. Until then, the
+    /// node is skipped. Defaults to 60 seconds.
+    pub fn healthcheck_interval(mut self, healthcheck_interval: Duration) -> Self {
+        self.healthcheck_interval = healthcheck_interval;
         self
     }
 
@@ -43,23 +198,149 @@ impl<T> ClientBuilder<T> {
         self
     }
 
+    /// Send the api key as the `x-typesense-api-key` query parameter
+    /// instead of the `X-TYPESENSE-API-KEY` header. Disabled by default.
+    ///
+    /// Browsers send a CORS preflight request before any request with a
+    /// custom header, which this avoids. The api key may then show up in
+    /// the logs of the proxies between the client and the cluster.
+    pub fn api_key_in_query(mut self, api_key_in_query: bool) -> Self {
+        self.api_key_in_query = api_key_in_query;
+        self
+    }
+
+    /// Add a header sent with every request. Headers set more than once
+    /// are sent with every value, and the api key header always wins.
+    pub fn default_header(mut self, name: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.default_headers
+            .push((name.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    /// Set the `User-Agent` header sent with every request.
+    ///
+    /// Browsers may not let it be set, and use their own instead.
+    pub fn user_agent(mut self, user_agent: impl AsRef<str>) -> Self {
+        self.user_agent = Some(user_agent.as_ref().to_string());
+        self
+    }
+
     /// Set transport
     pub fn transport(mut self, transport: Transport<T>) -> Self {
         self.transport = Some(transport);
         self
     }
+
+    /// Set the timeout of the requests, instead of the timeout of the transport.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the [`RetryPolicy`], instead of the one of the transport.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Set the [`MetricsRecorder`] told about every request of the client.
+    pub fn metrics_recorder(mut self, recorder: impl MetricsRecorder + 'static) -> Self {
+        self.metrics = Some(Arc::new(recorder));
+        self
+    }
+
+    /// Set the budget of the searches sent to each node.
+    /// See [`RequestLimit`]. Searches are not limited by default.
+    pub fn search_limit(mut self, limit: RequestLimit) -> Self {
+        self.limits.search = limit;
+        self
+    }
+
+    /// Set the budget of the writes sent to each node: imports, and the
+    /// creations, updates and deletions of any resource.
+    /// See [`RequestLimit`]. Writes are not limited by default.
+    pub fn write_limit(mut self, limit: RequestLimit) -> Self {
+        self.limits.write = limit;
+        self
+    }
+
+    /// Cache the results of up to `capacity` searches, for `ttl` each.
+    ///
+    /// Searches are cached by collection and parameters, the least recently
+    /// used one being dropped when the cache is full. Writing documents into
+    /// a collection through the client drops the cached results of the
+    /// searches in that collection. The cache is disabled by default.
+    pub fn search_cache(mut self, capacity: usize, ttl: Duration) -> Self {
+        self.search_cache = Some((capacity, ttl));
+        self
+    }
+
+    /// Coalesce the identical `GET` requests, searches included, sent while
+    /// one of them is in flight: only the first one is sent, and the others
+    /// receive a copy of its response. Disabled by default.
+    ///
+    /// Should the first request fail with an error that can't be copied,
+    /// such as a connection error, the others are sent on their own.
+    pub fn coalesce_requests(mut self, coalesce_requests: bool) -> Self {
+        self.coalesce_requests = coalesce_requests;
+        self
+    }
+
+    /// Hedge the slow `GET` requests, searches included: when a request has
+    /// not been answered after the delay of `hedge_policy`, it is sent again
+    /// to another node, and the first response wins. Disabled by default.
+    ///
+    /// This needs at least two nodes, and adds load to the cluster.
+    pub fn hedge_requests(mut self, hedge_policy: HedgePolicy) -> Self {
+        self.hedge_policy = Some(hedge_policy);
+        self
+    }
 }
 
 impl<T> Default for ClientBuilder<T> {
     fn default() -> Self {
         Self {
             transport: None,
-            host: None,
+            nodes: Vec::new(),
+            nearest_node: None,
+            healthcheck_interval: Duration::from_secs(60),
             api_key: None,
+            api_key_in_query: false,
+            default_headers: Vec::new(),
+            user_agent: None,
+            timeout: None,
+            retry_policy: None,
+            metrics: None,
+            limits: Limits::default(),
+            search_cache: None,
+            coalesce_requests: false,
+            hedge_policy: None,
+            config_error: None,
         }
     }
 }
 
+/// Check that a node is a URL with a scheme and a host, such as `http://localhost:8108`.
+fn validate_node(node: &str) -> Result<()> {
+    match node.parse::<http::Uri>() {
+        Ok(uri) if uri.scheme().is_some() && uri.host().is_some() => Ok(()),
+        _ => Err(TypesenseError::ConfigError(format!(
+            "invalid node {:?}, expected a URL such as http://localhost:8108",
+            node
+        ))),
+    }
+}
+
+fn header_name(name: &str) -> Result<http::HeaderName> {
+    http::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| TypesenseError::ConfigError(format!("invalid header name {:?}", name)))
+}
+
+fn header_value(name: &str, value: &str) -> Result<http::HeaderValue> {
+    http::HeaderValue::from_str(value)
+        .map_err(|_| TypesenseError::ConfigError(format!("invalid {} header value", name)))
+}
+
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
 #[cfg_attr(
     docsrs,
@@ -72,10 +353,35 @@ impl ClientBuilder<crate::transport::HyperHttpsClient> {
         let transport = Some(crate::transport::TransportBuilder::new_hyper().build());
         Self {
             transport,
-            host: None,
-            api_key: None,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "rustls", not(target_arch = "wasm32")))))]
+impl ClientBuilder<crate::transport::HyperRustlsClient> {
+    /// Create client builder with a [`hyper`](https://docs.rs/hyper) client
+    /// using [`rustls`](https://docs.rs/rustls), trusting the webpki roots.
+    pub fn new_hyper_rustls() -> Self {
+        let transport = Some(crate::transport::TransportBuilder::new_hyper_rustls().build());
+        Self {
+            transport,
+            ..Self::default()
         }
     }
+
+    /// Create client builder with a [`hyper`](https://docs.rs/hyper) client
+    /// using [`rustls`](https://docs.rs/rustls), with a custom
+    /// [`TlsConfig`](crate::transport::TlsConfig) (CA bundles, client certificates).
+    pub fn new_hyper_rustls_with_tls(tls: crate::transport::TlsConfig) -> Result<Self> {
+        let transport =
+            Some(crate::transport::TransportBuilder::new_hyper_rustls_with_tls(tls)?.build());
+        Ok(Self {
+            transport,
+            ..Self::default()
+        })
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -86,8 +392,71 @@ impl ClientBuilder<WasmClient> {
         let transport = Some(crate::transport::TransportBuilder::new_wasm().build());
         Self {
             transport,
-            host: None,
-            api_key: None,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::transport::{MockTransport, TransportBuilder};
+
+    fn builder() -> ClientBuilder<MockTransport> {
+        ClientBuilder::default().transport(TransportBuilder::new(MockTransport::new()).build())
+    }
+
+    #[test]
+    fn applies_config() {
+        let config: ClientConfig = serde_json::from_str(
+            r#"{"nodes": ["http://a:8108"], "api_key": "VerySecretKey", "timeout_ms": 2000}"#,
+        )
+        .unwrap();
+
+        let client = builder().config(config).build().unwrap();
+
+        assert_eq!(client.nodes_health()[0].host, "http://a:8108");
+        assert_eq!(client.timeout, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn invalid_config_is_an_error() {
+        let error = builder()
+            .host("localhost:8108")
+            .api_key("VerySecretKey")
+            .build()
+            .err()
+            .unwrap();
+        assert!(
+            matches!(error, TypesenseError::ConfigError(message) if message.contains("localhost:8108"))
+        );
+
+        let error = builder()
+            .host("http://localhost:8108")
+            .api_key("Very\nSecretKey")
+            .build()
+            .err()
+            .unwrap();
+        assert!(matches!(error, TypesenseError::ConfigError(_)));
+
+        let error = builder()
+            .host("http://localhost:8108")
+            .api_key("VerySecretKey")
+            .default_header("X Custom", "value")
+            .build()
+            .err()
+            .unwrap();
+        assert!(
+            matches!(error, TypesenseError::ConfigError(message) if message.contains("X Custom"))
+        );
+
+        for config in [
+            r#"{"nodes": ["http://a:8108"], "api_key": "VerySecretKey", "timeout_ms": 0}"#,
+            r#"{"nodes": ["http://a:8108"], "api_key": "VerySecretKey", "retry": {"max_attempts": 0}}"#,
+        ] {
+            let config: ClientConfig = serde_json::from_str(config).unwrap();
+            let error = builder().config(config).build().err().unwrap();
+            assert!(matches!(error, TypesenseError::ConfigError(_)));
         }
     }
 }
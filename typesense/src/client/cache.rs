@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use instant::Instant;
+
+/// A least recently used cache of the search results
+/// of a [`Client`](super::Client), by collection.
+///
+/// Every collection has a generation, bumped whenever it is written to,
+/// so that a search sent before a write can't fill the cache once the
+/// write is done.
+pub(crate) struct SearchCache {
+    capacity: usize,
+    ttl: Duration,
+    state: Mutex<State>,
+}
+
+/// A collection, and the query of a search in it.
+type Key = (String, String);
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<Key, Entry>,
+    /// The keys of the entries, from the least recently used one.
+    recency: BTreeMap<u64, Key>,
+    tick: u64,
+    generations: HashMap<String, u64>,
+}
+
+struct Entry {
+    body: Arc<[u8]>,
+    expires_at: Instant,
+    used_at: u64,
+}
+
+impl SearchCache {
+    pub(crate) fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            state: Mutex::default(),
+        }
+    }
+
+    /// The body of the result of the search `query` in `collection`, unless
+    /// it is not cached or has expired, along with the current generation
+    /// of the collection.
+    pub(crate) fn get(&self, collection: &str, query: &str) -> (Option<Arc<[u8]>>, u64) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let generation = state.generations.get(collection).copied().unwrap_or(0);
+        let key = (collection.to_string(), query.to_string());
+
+        let entry = match state.entries.get_mut(&key) {
+            Some(entry) => entry,
+            None => return (None, generation),
+        };
+        state.recency.remove(&entry.used_at);
+
+        if entry.expires_at <= Instant::now() {
+            state.entries.remove(&key);
+            return (None, generation);
+        }
+
+        state.tick += 1;
+        entry.used_at = state.tick;
+        let body = entry.body.clone();
+        state.recency.insert(state.tick, key);
+
+        (Some(body), generation)
+    }
+
+    /// Cache the result of a search, unless `collection` was written
+    /// to since `generation`, evicting the least recently used entry
+    /// when the cache is full.
+    pub(crate) fn insert(&self, collection: &str, query: &str, body: &[u8], generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.generations.get(collection).copied().unwrap_or(0) != generation {
+            return;
+        }
+
+        state.tick += 1;
+        let key = (collection.to_string(), query.to_string());
+        let entry = Entry {
+            body: body.into(),
+            expires_at: Instant::now() + self.ttl,
+            used_at: state.tick,
+        };
+
+        if let Some(previous) = state.entries.insert(key.clone(), entry) {
+            state.recency.remove(&previous.used_at);
+        }
+        let tick = state.tick;
+        state.recency.insert(tick, key);
+
+        while state.entries.len() > self.capacity {
+            let (_, key) = state
+                .recency
+                .pop_first()
+                .expect("every entry has a recency");
+            state.entries.remove(&key);
+        }
+    }
+
+    /// Drop the results of the searches in `collection`.
+    pub(crate) fn invalidate(&self, collection: &str) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        *state.generations.entry(collection.to_string()).or_default() += 1;
+        let recency = &mut state.recency;
+        state.entries.retain(|(entry_collection, _), entry| {
+            let keep = entry_collection != collection;
+            if !keep {
+                recency.remove(&entry.used_at);
+            }
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(cache: &SearchCache, collection: &str, query: &str) -> Option<Vec<u8>> {
+        cache.get(collection, query).0.map(|body| body.to_vec())
+    }
+
+    #[test]
+    fn evicts_least_recently_used_searches() {
+        let cache = SearchCache::new(2, Duration::from_secs(60));
+        cache.insert("companies", "q=a", b"a", 0);
+        cache.insert("companies", "q=b", b"b", 0);
+
+        assert_eq!(cached(&cache, "companies", "q=a"), Some(b"a".to_vec()));
+        cache.insert("companies", "q=c", b"c", 0);
+
+        assert_eq!(cached(&cache, "companies", "q=a"), Some(b"a".to_vec()));
+        assert_eq!(cached(&cache, "companies", "q=b"), None);
+        assert_eq!(cached(&cache, "companies", "q=c"), Some(b"c".to_vec()));
+    }
+
+    #[test]
+    fn expires_searches() {
+        let cache = SearchCache::new(2, Duration::from_secs(0));
+        cache.insert("companies", "q=a", b"a", 0);
+
+        assert_eq!(cached(&cache, "companies", "q=a"), None);
+        assert!(cache.state.lock().unwrap().recency.is_empty());
+    }
+
+    #[test]
+    fn writes_invalidate_their_collection() {
+        let cache = SearchCache::new(4, Duration::from_secs(60));
+        cache.insert("companies", "q=a", b"a", 0);
+        cache.insert("people", "q=a", b"a", 0);
+        let (_, generation) = cache.get("companies", "q=b");
+
+        cache.invalidate("companies");
+        cache.insert("companies", "q=b", b"b", generation);
+
+        assert_eq!(cached(&cache, "companies", "q=a"), None);
+        assert_eq!(cached(&cache, "companies", "q=b"), None);
+        assert_eq!(cached(&cache, "people", "q=a"), Some(b"a".to_vec()));
+        assert_eq!(cache.get("companies", "q=a").1, 1);
+    }
+}
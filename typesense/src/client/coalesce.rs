@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use futures_channel::oneshot;
+use http::Response;
+
+use crate::Result;
+
+/// The result shared with a waiting request, or `None` when the
+/// waiting request has to be sent on its own.
+type Shared = Option<Result<Response<Vec<u8>>>>;
+
+/// Coalesces the identical requests of a [`Client`](super::Client)
+/// sent concurrently into a single one.
+///
+/// The first request is sent, and the identical ones sent while it is
+/// in flight wait for its response. If it is cancelled, or fails with an
+/// error that can't be copied, the waiting requests are sent on their own.
+#[derive(Default)]
+pub(crate) struct Coalescer {
+    in_flight: Mutex<HashMap<String, Vec<oneshot::Sender<Shared>>>>,
+}
+
+impl Coalescer {
+    /// Send the request identified by `key` with `send`, unless an identical
+    /// one is already in flight, then waiting for its response instead.
+    pub(crate) async fn coalesce<F, Fut>(&self, key: &str, send: F) -> Result<Response<Vec<u8>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Response<Vec<u8>>>>,
+    {
+        let waiting = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(key) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(key.to_string(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        if let Some(receiver) = waiting {
+            return match receiver.await {
+                Ok(Some(result)) => result,
+                Ok(None) | Err(oneshot::Canceled) => send().await,
+            };
+        }
+
+        // Should the request be cancelled, the waiting requests are
+        // woken up when the guard is dropped, along with their senders.
+        let guard = InFlight {
+            coalescer: self,
+            key,
+        };
+        let result = send().await;
+
+        for waiter in guard.finish() {
+            let _ = waiter.send(share(&result));
+        }
+        result
+    }
+}
+
+/// A request in flight, removed from its [`Coalescer`] when dropped.
+struct InFlight<'a> {
+    coalescer: &'a Coalescer,
+    key: &'a str,
+}
+
+impl InFlight<'_> {
+    /// The requests waiting for the response of this one.
+    fn finish(self) -> Vec<oneshot::Sender<Shared>> {
+        let waiters = self.coalescer.in_flight.lock().unwrap().remove(self.key);
+        // The key may already be used by the next identical request.
+        std::mem::forget(self);
+        waiters.unwrap_or_default()
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut in_flight) = self.coalescer.in_flight.lock() {
+            in_flight.remove(self.key);
+        }
+    }
+}
+
+fn share(result: &Result<Response<Vec<u8>>>) -> Shared {
+    match result {
+        Ok(response) => {
+            let mut shared = Response::new(response.body().clone());
+            *shared.status_mut() = response.status();
+            *shared.version_mut() = response.version();
+            *shared.headers_mut() = response.headers().clone();
+            Some(Ok(shared))
+        }
+        Err(error) => error.try_clone().map(Err),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::FutureExt;
+    use http::StatusCode;
+
+    use super::*;
+    use crate::TypesenseError;
+
+    #[tokio::test]
+    async fn identical_requests_share_a_response() {
+        let coalescer = Coalescer::default();
+        let sent = AtomicUsize::new(0);
+        let (respond, response) = oneshot::channel::<()>();
+        let response = response.shared();
+
+        let send = || {
+            sent.fetch_add(1, Ordering::SeqCst);
+            let response = response.clone();
+            async move {
+                response.await.unwrap();
+                Ok(Response::new(b"{}".to_vec()))
+            }
+        };
+        let requests = futures_util::future::join3(
+            coalescer.coalesce("/keys", send),
+            coalescer.coalesce("/keys", send),
+            async {
+                tokio::task::yield_now().await;
+                respond.send(()).unwrap();
+            },
+        );
+
+        let (first, second, ()) = requests.await;
+        assert_eq!(first.unwrap().body(), b"{}");
+        assert_eq!(second.unwrap().body(), b"{}");
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+        assert!(coalescer.in_flight.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn waiting_requests_are_sent_when_error_is_not_shared() {
+        let coalescer = Coalescer::default();
+        let (fail, failure) = oneshot::channel::<()>();
+
+        let requests = futures_util::future::join3(
+            coalescer.coalesce("/keys", || async {
+                failure.await.unwrap();
+                Err(std::io::Error::other("reset").into())
+            }),
+            coalescer.coalesce("/keys", || async { Err(StatusCode::NOT_FOUND.into()) }),
+            async {
+                tokio::task::yield_now().await;
+                fail.send(()).unwrap();
+            },
+        );
+
+        let (first, second, ()) = requests.await;
+        assert!(matches!(first, Err(TypesenseError::IoError(_))));
+        assert!(matches!(second, Err(TypesenseError::ObjectNotFound(_))));
+    }
+}
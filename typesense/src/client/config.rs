@@ -0,0 +1,223 @@
+use std::env::{self, VarError};
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::transport::RetryPolicy;
+use crate::{Result, TypesenseError};
+
+/// The configuration of a [`Client`](super::Client), to embed in the
+/// configuration of an application, or to read from the environment.
+///
+/// Every setting is optional, the missing ones keep the value
+/// of the [`ClientBuilder`](super::ClientBuilder) it is applied to
+/// with [`ClientBuilder::config`](super::ClientBuilder::config).
+/// The settings are validated when the client is built.
+///
+/// The API key is neither serialized nor shown by [`Debug`](std::fmt::Debug),
+/// so the configuration can be logged.
+///
+/// ```
+/// let config: typesense::ClientConfig = serde_json::from_str(r#"{
+///     "nodes": ["https://a.example.com", "https://b.example.com"],
+///     "api_key": "VerySecretKey",
+///     "timeout_ms": 2000,
+///     "retry": { "max_attempts": 5 }
+/// }"#).unwrap();
+/// ```
+#[derive(Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ClientConfig {
+    /// The nodes of the cluster. See [`ClientBuilder::nodes`](super::ClientBuilder::nodes).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<String>,
+    /// The nearest node. See [`ClientBuilder::nearest_node`](super::ClientBuilder::nearest_node).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nearest_node: Option<String>,
+    /// The API key.
+    #[serde(skip_serializing)]
+    pub api_key: Option<String>,
+    /// The timeout of the requests, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// The interval after which a failed node is probed again, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck_interval_ms: Option<u64>,
+    /// The retries of the failed requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+}
+
+/// The settings of a [`RetryPolicy`], the missing ones keeping their default.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct RetryConfig {
+    /// See [`RetryPolicy::max_attempts`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<usize>,
+    /// See [`RetryPolicy::base_backoff`], in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_backoff_ms: Option<u64>,
+    /// See [`RetryPolicy::max_backoff`], in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl ClientConfig {
+    /// Read the configuration from the environment variables:
+    ///
+    /// - `TYPESENSE_NODES`, the nodes separated by commas,
+    /// - `TYPESENSE_NEAREST_NODE`,
+    /// - `TYPESENSE_API_KEY`,
+    /// - `TYPESENSE_TIMEOUT_MS`,
+    /// - `TYPESENSE_HEALTHCHECK_INTERVAL_MS`,
+    /// - `TYPESENSE_RETRY_MAX_ATTEMPTS`,
+    /// - `TYPESENSE_RETRY_BASE_BACKOFF_MS`,
+    /// - `TYPESENSE_RETRY_MAX_BACKOFF_MS`.
+    ///
+    /// Returns a [`TypesenseError::ConfigError`] if a variable can't be parsed.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|name| env::var(name))
+    }
+
+    fn from_vars(vars: impl Fn(&str) -> std::result::Result<String, VarError>) -> Result<Self> {
+        let string = |name| string(&vars, name);
+        let number = |name| number(&vars, name);
+
+        let retry = RetryConfig {
+            max_attempts: number("TYPESENSE_RETRY_MAX_ATTEMPTS")?.map(|n| n as usize),
+            base_backoff_ms: number("TYPESENSE_RETRY_BASE_BACKOFF_MS")?,
+            max_backoff_ms: number("TYPESENSE_RETRY_MAX_BACKOFF_MS")?,
+        };
+
+        Ok(Self {
+            nodes: string("TYPESENSE_NODES")?
+                .map(|nodes| {
+                    nodes
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|node| !node.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            nearest_node: string("TYPESENSE_NEAREST_NODE")?,
+            api_key: string("TYPESENSE_API_KEY")?,
+            timeout_ms: number("TYPESENSE_TIMEOUT_MS")?,
+            healthcheck_interval_ms: number("TYPESENSE_HEALTHCHECK_INTERVAL_MS")?,
+            retry: Some(retry).filter(|retry| *retry != RetryConfig::default()),
+        })
+    }
+}
+
+impl fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientConfig")
+            .field("nodes", &self.nodes)
+            .field("nearest_node", &self.nearest_node)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("timeout_ms", &self.timeout_ms)
+            .field("healthcheck_interval_ms", &self.healthcheck_interval_ms)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+impl RetryConfig {
+    pub(crate) fn policy(&self) -> RetryPolicy {
+        let mut policy = RetryPolicy::new();
+        if let Some(max_attempts) = self.max_attempts {
+            policy = policy.max_attempts(max_attempts);
+        }
+        if let Some(base_backoff_ms) = self.base_backoff_ms {
+            policy = policy.base_backoff(Duration::from_millis(base_backoff_ms));
+        }
+        if let Some(max_backoff_ms) = self.max_backoff_ms {
+            policy = policy.max_backoff(Duration::from_millis(max_backoff_ms));
+        }
+        policy
+    }
+}
+
+type Vars<'a> = &'a dyn Fn(&str) -> std::result::Result<String, VarError>;
+
+fn string(vars: Vars<'_>, name: &str) -> Result<Option<String>> {
+    match vars(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err(TypesenseError::ConfigError(format!(
+            "{} is not valid unicode",
+            name
+        ))),
+    }
+}
+
+fn number(vars: Vars<'_>, name: &str) -> Result<Option<u64>> {
+    string(vars, name)?
+        .map(|value| {
+            value.trim().parse().map_err(|e| {
+                TypesenseError::ConfigError(format!("invalid {} {:?}: {}", name, value, e))
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn from_vars(vars: &[(&str, &str)]) -> Result<ClientConfig> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        ClientConfig::from_vars(|name| {
+            vars.get(name)
+                .map(|value| value.to_string())
+                .ok_or(VarError::NotPresent)
+        })
+    }
+
+    #[test]
+    fn reads_environment() {
+        let config = from_vars(&[
+            ("TYPESENSE_NODES", "http://a:8108, http://b:8108,"),
+            ("TYPESENSE_API_KEY", "VerySecretKey"),
+            ("TYPESENSE_TIMEOUT_MS", "2000"),
+            ("TYPESENSE_RETRY_MAX_ATTEMPTS", "5"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.nodes, ["http://a:8108", "http://b:8108"]);
+        assert_eq!(config.api_key.as_deref(), Some("VerySecretKey"));
+        assert_eq!(config.timeout_ms, Some(2000));
+        assert_eq!(config.retry.unwrap().max_attempts, Some(5));
+        assert_eq!(from_vars(&[]).unwrap(), ClientConfig::default());
+
+        let error = from_vars(&[("TYPESENSE_TIMEOUT_MS", "2s")]).unwrap_err();
+        assert!(
+            matches!(error, TypesenseError::ConfigError(message) if message.contains("TYPESENSE_TIMEOUT_MS"))
+        );
+    }
+
+    #[test]
+    fn hides_api_key() {
+        let config = from_vars(&[("TYPESENSE_API_KEY", "VerySecretKey")]).unwrap();
+
+        assert!(!format!("{:?}", config).contains("VerySecretKey"));
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("VerySecretKey"));
+    }
+
+    #[test]
+    fn rejects_unknown_settings() {
+        let config: ClientConfig =
+            serde_json::from_str(r#"{"nodes": ["http://a:8108"], "retry": {}}"#).unwrap();
+        assert_eq!(config.nodes, ["http://a:8108"]);
+
+        assert!(serde_json::from_str::<ClientConfig>(r#"{"node": "http://a:8108"}"#).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The number of latencies the hedging delay is computed from.
+const WINDOW: usize = 256;
+
+/// The number of latencies needed before the hedging delay is computed from them.
+const MIN_SAMPLES: usize = 20;
+
+/// The policy of the hedged requests of a [`Client`](super::Client).
+///
+/// When a search or a `GET` request has not been answered after a delay,
+/// the same request is sent to another node, and the first response wins,
+/// the other request being cancelled. The delay is a percentile of the
+/// latencies of the last requests, so that only the slowest requests are
+/// hedged.
+///
+/// By default, the requests slower than 95% of the last ones are hedged,
+/// after 100ms until enough requests were sent to compute the percentile.
+#[derive(Clone, Debug)]
+pub struct HedgePolicy {
+    percentile: f64,
+    initial_delay: Duration,
+    min_delay: Duration,
+}
+
+impl HedgePolicy {
+    /// Create a hedge policy with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the percentile, between 0 and 1, of the latencies
+    /// after which a request is hedged.
+    pub fn percentile(mut self, percentile: f64) -> Self {
+        self.percentile = percentile.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the delay after which a request is hedged, until
+    /// enough requests were sent to compute the percentile.
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Set the minimum delay after which a request is hedged,
+    /// so that the fastest nodes don't get twice the requests.
+    pub fn min_delay(mut self, min_delay: Duration) -> Self {
+        self.min_delay = min_delay;
+        self
+    }
+}
+
+impl Default for HedgePolicy {
+    fn default() -> Self {
+        Self {
+            percentile: 0.95,
+            initial_delay: Duration::from_millis(100),
+            min_delay: Duration::from_millis(5),
+        }
+    }
+}
+
+/// The hedge policy of a client, along with the latencies of its last requests.
+pub(crate) struct Hedging {
+    policy: HedgePolicy,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl Hedging {
+    pub(crate) fn new(policy: HedgePolicy) -> Self {
+        Self {
+            policy,
+            latencies: Mutex::new(VecDeque::with_capacity(WINDOW)),
+        }
+    }
+
+    /// The delay after which a request is hedged.
+    pub(crate) fn delay(&self) -> Duration {
+        let mut latencies: Vec<_> = {
+            let latencies = self.latencies.lock().unwrap();
+            if latencies.len() < MIN_SAMPLES {
+                return self.policy.initial_delay;
+            }
+            latencies.iter().copied().collect()
+        };
+
+        let index = ((latencies.len() - 1) as f64 * self.policy.percentile).round() as usize;
+        let (_, delay, _) = latencies.select_nth_unstable(index);
+        (*delay).max(self.policy.min_delay)
+    }
+
+    /// Record the latency of an attempt answered by its node.
+    pub(crate) fn record(&self, latency: Duration) {
+        let mut latencies = self.latencies.lock().unwrap();
+        if latencies.len() == WINDOW {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+}
+
+/// A request being hedged, whose sends never try the same node twice.
+pub(crate) struct HedgedRequest<'a> {
+    pub(crate) hedging: &'a Hedging,
+    tried: Mutex<Vec<Arc<String>>>,
+}
+
+impl<'a> HedgedRequest<'a> {
+    pub(crate) fn new(hedging: &'a Hedging) -> Self {
+        Self {
+            hedging,
+            tried: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether no send of the request tried `host` yet, which it is then
+    /// tried by.
+    pub(crate) fn try_node(&self, host: &Arc<String>) -> bool {
+        let mut tried = self.tried.lock().unwrap();
+        if tried.contains(host) {
+            false
+        } else {
+            tried.push(host.clone());
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_is_a_percentile_of_latencies() {
+        let hedging = Hedging::new(
+            HedgePolicy::new()
+                .percentile(0.9)
+                .initial_delay(Duration::from_millis(100))
+                .min_delay(Duration::from_millis(5)),
+        );
+        assert_eq!(hedging.delay(), Duration::from_millis(100));
+
+        for latency in (1..=MIN_SAMPLES as u64).rev() {
+            hedging.record(Duration::from_millis(latency * 10));
+        }
+        assert_eq!(hedging.delay(), Duration::from_millis(180));
+
+        for _ in 0..WINDOW {
+            hedging.record(Duration::from_millis(1));
+        }
+        assert_eq!(hedging.delay(), Duration::from_millis(5));
+    }
+}
@@ -0,0 +1,318 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use instant::Instant;
+
+use super::metrics::Endpoint;
+use crate::{Result, TypesenseError};
+
+/// A budget for the requests the [`Client`](super::Client) sends to each node.
+///
+/// The searches and the writes have a budget of their own, set with
+/// [`ClientBuilder::search_limit`](super::ClientBuilder::search_limit) and
+/// [`ClientBuilder::write_limit`](super::ClientBuilder::write_limit), so that
+/// a bulk import does not starve the searches sent through the same client.
+/// Requests over budget wait for their turn rather than failing.
+///
+/// ```
+/// use typesense::RequestLimit;
+///
+/// // At most 4 writes in flight per node, and 20 per second with bursts of 50.
+/// let limit = RequestLimit::new().max_in_flight(4).rate(20.0, 50);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequestLimit {
+    max_in_flight: Option<usize>,
+    rate: Option<(f64, u32)>,
+}
+
+impl RequestLimit {
+    /// A budget without any limit.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of requests in flight to a node at once.
+    ///
+    /// A request whose response is streamed is in flight
+    /// until the head of its response is received.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// Set the rate of the requests sent to a node, as a token bucket
+    /// refilled with `per_second` tokens every second, holding up to
+    /// `burst` tokens. Every request takes a token from the bucket.
+    pub fn rate(mut self, per_second: f64, burst: u32) -> Self {
+        self.rate = Some((per_second, burst));
+        self
+    }
+
+    pub(crate) fn validate(&self, name: &str) -> Result<()> {
+        if self.max_in_flight == Some(0) {
+            return Err(TypesenseError::ConfigError(format!(
+                "{} limit allows no request in flight",
+                name
+            )));
+        }
+        match self.rate {
+            Some((per_second, burst))
+                if !(per_second.is_finite() && per_second > 0.0) || burst == 0 =>
+            {
+                Err(TypesenseError::ConfigError(format!(
+                    "{} limit rate must be positive, with a burst of at least one request",
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The budgets of the requests sent to every node.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Limits {
+    pub(crate) search: RequestLimit,
+    pub(crate) write: RequestLimit,
+}
+
+/// The kind of traffic a request is accounted as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Traffic {
+    Search,
+    Write,
+}
+
+impl Traffic {
+    /// Searches, including the multi searches sent with `POST`, are
+    /// accounted as search traffic, and the other requests changing
+    /// data as writes. The remaining reads are not limited.
+    pub(crate) fn of(method: &http::Method, path: &str) -> Option<Self> {
+        if Endpoint::classify(path).0 == Endpoint::Search {
+            Some(Traffic::Search)
+        } else if matches!(*method, http::Method::GET | http::Method::HEAD) {
+            None
+        } else {
+            Some(Traffic::Write)
+        }
+    }
+}
+
+/// A slot in the requests in flight to a node, freed when dropped.
+pub(crate) type Permit = Option<SemaphoreGuardArc>;
+
+/// Enforces the budgets of a single node.
+pub(crate) struct NodeLimiter {
+    search: Limiter,
+    write: Limiter,
+}
+
+impl NodeLimiter {
+    pub(crate) fn new(limits: &Limits) -> Self {
+        Self {
+            search: Limiter::new(&limits.search),
+            write: Limiter::new(&limits.write),
+        }
+    }
+
+    /// Wait until a request of `traffic` can be sent.
+    pub(crate) async fn acquire(&self, traffic: Option<Traffic>) -> Permit {
+        match traffic {
+            Some(Traffic::Search) => self.search.acquire().await,
+            Some(Traffic::Write) => self.write.acquire().await,
+            None => None,
+        }
+    }
+}
+
+struct Limiter {
+    in_flight: Option<Arc<Semaphore>>,
+    bucket: Option<Mutex<Bucket>>,
+}
+
+struct Bucket {
+    per_second: f64,
+    burst: f64,
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Limiter {
+    fn new(limit: &RequestLimit) -> Self {
+        Self {
+            in_flight: limit.max_in_flight.map(|max| Arc::new(Semaphore::new(max))),
+            bucket: limit.rate.map(|(per_second, burst)| {
+                Mutex::new(Bucket {
+                    per_second,
+                    burst: burst as f64,
+                    tokens: burst as f64,
+                    updated_at: Instant::now(),
+                })
+            }),
+        }
+    }
+
+    async fn acquire(&self) -> Permit {
+        let mut token = Token(None);
+        if let Some(bucket) = &self.bucket {
+            let wait = bucket.lock().unwrap().take(Instant::now());
+            token = Token(Some(bucket));
+            if wait > Duration::ZERO {
+                futures_timer::Delay::new(wait).await;
+            }
+        }
+
+        let permit = match &self.in_flight {
+            Some(in_flight) => Some(in_flight.acquire_arc().await),
+            None => None,
+        };
+        token.spend();
+        permit
+    }
+}
+
+/// A token taken from a bucket, given back when dropped unless it is
+/// spent, so that a request cancelled while waiting doesn't use the
+/// budget of the others.
+struct Token<'a>(Option<&'a Mutex<Bucket>>);
+
+impl Token<'_> {
+    fn spend(mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for Token<'_> {
+    fn drop(&mut self) {
+        if let Some(Ok(mut bucket)) = self.0.map(Mutex::lock) {
+            bucket.give_back();
+        }
+    }
+}
+
+impl Bucket {
+    /// Take a token, returning how long to wait until it is available.
+    ///
+    /// The bucket goes into debt when empty, so that the
+    /// waiting requests are sent in the order they came.
+    fn take(&mut self, now: Instant) -> Duration {
+        let elapsed = now.duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.burst) - 1.0;
+        self.updated_at = now;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+
+    /// Give back a token that was not used.
+    fn give_back(&mut self) {
+        self.tokens = (self.tokens + 1.0).min(self.burst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_traffic() {
+        use http::Method;
+
+        let search = "/collections/companies/documents/search?q=stark";
+        assert_eq!(Traffic::of(&Method::GET, search), Some(Traffic::Search));
+        assert_eq!(
+            Traffic::of(&Method::POST, "/multi_search"),
+            Some(Traffic::Search)
+        );
+        assert_eq!(
+            Traffic::of(&Method::POST, "/collections/companies/documents/import"),
+            Some(Traffic::Write)
+        );
+        assert_eq!(
+            Traffic::of(&Method::DELETE, "/collections/companies"),
+            Some(Traffic::Write)
+        );
+        assert_eq!(Traffic::of(&Method::GET, "/collections/companies"), None);
+    }
+
+    #[test]
+    fn bucket_refills_at_its_rate() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            per_second: 10.0,
+            burst: 2.0,
+            tokens: 2.0,
+            updated_at: start,
+        };
+
+        assert_eq!(bucket.take(start), Duration::ZERO);
+        assert_eq!(bucket.take(start), Duration::ZERO);
+        assert_eq!(bucket.take(start), Duration::from_millis(100));
+        assert_eq!(bucket.take(start), Duration::from_millis(200));
+
+        // Once the debt is paid, the bucket fills up to its burst only.
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.take(later), Duration::ZERO);
+        assert_eq!(bucket.take(later), Duration::ZERO);
+        assert!(bucket.take(later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn cancelled_requests_give_back_their_token() {
+        use futures_util::FutureExt;
+
+        let limiter = Limiter::new(&RequestLimit::new().rate(1.0, 1));
+        let tokens = || limiter.bucket.as_ref().unwrap().lock().unwrap().tokens;
+
+        assert!(limiter.acquire().now_or_never().is_some());
+        assert!(limiter.acquire().now_or_never().is_none());
+        assert!(tokens() > -0.5);
+    }
+
+    #[test]
+    fn writes_do_not_starve_searches() {
+        use futures_util::FutureExt;
+
+        let limiter = NodeLimiter::new(&Limits {
+            search: RequestLimit::new().max_in_flight(1),
+            write: RequestLimit::new().max_in_flight(1),
+        });
+
+        let write = limiter
+            .acquire(Some(Traffic::Write))
+            .now_or_never()
+            .unwrap();
+        assert!(write.is_some());
+        assert!(limiter
+            .acquire(Some(Traffic::Write))
+            .now_or_never()
+            .is_none());
+        assert!(limiter
+            .acquire(Some(Traffic::Search))
+            .now_or_never()
+            .is_some());
+        assert!(limiter.acquire(None).now_or_never().is_some());
+
+        drop(write);
+        assert!(limiter
+            .acquire(Some(Traffic::Write))
+            .now_or_never()
+            .is_some());
+    }
+
+    #[test]
+    fn invalid_limits_are_config_errors() {
+        assert!(RequestLimit::new().validate("search").is_ok());
+        assert!(RequestLimit::new()
+            .max_in_flight(0)
+            .validate("search")
+            .is_err());
+        assert!(RequestLimit::new().rate(0.0, 1).validate("write").is_err());
+        assert!(RequestLimit::new().rate(1.0, 0).validate("write").is_err());
+    }
+}
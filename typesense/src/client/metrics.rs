@@ -0,0 +1,403 @@
+//! Hooks to collect metrics about the requests sent by the [`Client`](crate::Client).
+//!
+//! A [`MetricsRecorder`], set with
+//! [`ClientBuilder::metrics_recorder`](crate::ClientBuilder::metrics_recorder),
+//! is told when every request starts and finishes, with the kind of endpoint
+//! it targets, so that latencies and errors can be counted per endpoint.
+//!
+//! ```
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! use typesense::metrics::{MetricsRecorder, RequestFinish, StatusClass};
+//!
+//! #[derive(Default)]
+//! struct ErrorCounter(AtomicUsize);
+//!
+//! impl MetricsRecorder for ErrorCounter {
+//!     fn on_request_finish(&self, request: &RequestFinish<'_>) {
+//!         if request.status_class != StatusClass::Success {
+//!             self.0.fetch_add(1, Ordering::Relaxed);
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::{Method, Response, StatusCode};
+
+use super::trace;
+use crate::Result;
+
+/// Receives an event when a request of the [`Client`](crate::Client)
+/// starts, and another one when it finishes.
+///
+/// The events are sent from the tasks sending the requests,
+/// so they should be handled without blocking.
+pub trait MetricsRecorder: Send + Sync {
+    /// Called before a request is sent to any node.
+    fn on_request_start(&self, _request: &RequestStart<'_>) {}
+
+    /// Called once a request is done, successful or not,
+    /// after it was retried on the other nodes if needed,
+    /// or once it is dropped before being done.
+    fn on_request_finish(&self, _request: &RequestFinish<'_>) {}
+}
+
+impl<R: MetricsRecorder + ?Sized> MetricsRecorder for Arc<R> {
+    fn on_request_start(&self, request: &RequestStart<'_>) {
+        (**self).on_request_start(request)
+    }
+
+    fn on_request_finish(&self, request: &RequestFinish<'_>) {
+        (**self).on_request_finish(request)
+    }
+}
+
+/// The kind of endpoint a request targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Endpoint {
+    /// A search, in one collection or through `/multi_search`.
+    Search,
+    /// An import of documents.
+    Import,
+    /// An export of documents.
+    Export,
+    /// The creation, retrieval, update or deletion of a document.
+    Documents,
+    /// The creation, retrieval, update or deletion of a collection.
+    Collection,
+    /// The Keys API.
+    Keys,
+    /// The health check of a node.
+    Health,
+    /// Any other endpoint.
+    Other,
+}
+
+impl Endpoint {
+    /// Classify a request from its path, returning the name
+    /// of the collection it targets too, if any.
+    pub(crate) fn classify(path: &str) -> (Self, Option<&str>) {
+        let path = path.split('?').next().unwrap_or_default();
+        let mut segments = path.split('/').filter(|segment| !segment.is_empty());
+
+        match segments.next() {
+            Some("collections") => {
+                let collection = segments.next();
+                let endpoint = match (segments.next(), segments.next()) {
+                    (Some("documents"), Some("search")) => Endpoint::Search,
+                    (Some("documents"), Some("import")) => Endpoint::Import,
+                    (Some("documents"), Some("export")) => Endpoint::Export,
+                    (Some("documents"), _) => Endpoint::Documents,
+                    (None, _) => Endpoint::Collection,
+                    _ => Endpoint::Other,
+                };
+                (endpoint, collection)
+            }
+            Some("multi_search") => (Endpoint::Search, None),
+            Some("keys") => (Endpoint::Keys, None),
+            Some("health") => (Endpoint::Health, None),
+            _ => (Endpoint::Other, None),
+        }
+    }
+
+    /// The name of the endpoint, to label metrics with.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::Search => "search",
+            Endpoint::Import => "import",
+            Endpoint::Export => "export",
+            Endpoint::Documents => "documents",
+            Endpoint::Collection => "collection",
+            Endpoint::Keys => "keys",
+            Endpoint::Health => "health",
+            Endpoint::Other => "other",
+        }
+    }
+}
+
+/// The class of the outcome of a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// The server answered with a 2xx status.
+    Success,
+    /// The server answered with a 4xx status.
+    ClientError,
+    /// The server answered with a 5xx status.
+    ServerError,
+    /// The server answered with any other status.
+    Other,
+    /// No status was received: the request could not be
+    /// sent, timed out, or its response could not be read.
+    Failure,
+    /// The request was dropped before it was done, like when
+    /// it is cancelled by a timeout of the caller.
+    Cancelled,
+}
+
+impl StatusClass {
+    fn of(status: Option<StatusCode>) -> Self {
+        match status {
+            Some(status) if status.is_success() => StatusClass::Success,
+            Some(status) if status.is_client_error() => StatusClass::ClientError,
+            Some(status) if status.is_server_error() => StatusClass::ServerError,
+            Some(_) => StatusClass::Other,
+            None => StatusClass::Failure,
+        }
+    }
+
+    /// The name of the class, to label metrics with.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatusClass::Success => "success",
+            StatusClass::ClientError => "client_error",
+            StatusClass::ServerError => "server_error",
+            StatusClass::Other => "other",
+            StatusClass::Failure => "failure",
+            StatusClass::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// A request about to be sent.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RequestStart<'a> {
+    /// The kind of endpoint the request targets.
+    pub endpoint: Endpoint,
+    /// The collection the request targets, if any.
+    pub collection: Option<&'a str>,
+    /// The method of the request.
+    pub method: &'a Method,
+    /// The path of the request, with its query.
+    pub path: &'a str,
+}
+
+/// A request that is done.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct RequestFinish<'a> {
+    /// The kind of endpoint the request targets.
+    pub endpoint: Endpoint,
+    /// The collection the request targets, if any.
+    pub collection: Option<&'a str>,
+    /// The method of the request.
+    pub method: &'a Method,
+    /// The path of the request, with its query.
+    pub path: &'a str,
+    /// The last node the request was sent to, if any.
+    pub node: Option<&'a str>,
+    /// The number of nodes the request was retried on.
+    pub retries: usize,
+    /// The status of the response, if one was received.
+    pub status: Option<StatusCode>,
+    /// The class of the outcome of the request.
+    pub status_class: StatusClass,
+    /// The time from the start of the request to its end,
+    /// including the retries. For a streamed response, the
+    /// body may still be being received.
+    pub latency: Duration,
+}
+
+/// The metrics of a single request, from its start to its end.
+///
+/// A request dropped before being [finished](Self::finish)
+/// is reported as [`StatusClass::Cancelled`].
+pub(crate) struct Metered<'a> {
+    recorder: Option<&'a dyn MetricsRecorder>,
+    endpoint: Endpoint,
+    collection: Option<&'a str>,
+    method: &'a Method,
+    path: &'a str,
+    node: Mutex<(Option<Arc<String>>, usize)>,
+    started: instant::Instant,
+    finished: bool,
+}
+
+impl<'a> Metered<'a> {
+    pub(crate) fn start(
+        recorder: Option<&'a dyn MetricsRecorder>,
+        method: &'a Method,
+        path: &'a str,
+    ) -> Self {
+        let (endpoint, collection) = Endpoint::classify(path);
+
+        if let Some(recorder) = recorder {
+            recorder.on_request_start(&RequestStart {
+                endpoint,
+                collection,
+                method,
+                path,
+            });
+        }
+
+        Self {
+            recorder,
+            endpoint,
+            collection,
+            method,
+            path,
+            node: Mutex::new((None, 0)),
+            started: instant::Instant::now(),
+            finished: false,
+        }
+    }
+
+    /// Record the node the request is sent to, after `retries` failed attempts.
+    pub(crate) fn set_node(&self, node: &Arc<String>, retries: usize) {
+        trace::record_node(node, retries);
+        *self.node.lock().unwrap() = (Some(node.clone()), retries);
+    }
+
+    pub(crate) fn finish<B>(mut self, result: &Result<Response<B>>) {
+        let status = match result {
+            Ok(response) => Some(response.status()),
+            Err(error) => error.api_error().map(|error| error.status),
+        };
+
+        self.report(status, StatusClass::of(status));
+        self.finished = true;
+    }
+
+    fn report(&self, status: Option<StatusCode>, status_class: StatusClass) {
+        let recorder = match self.recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        let (node, retries) = self.node.lock().unwrap().clone();
+
+        recorder.on_request_finish(&RequestFinish {
+            endpoint: self.endpoint,
+            collection: self.collection,
+            method: self.method,
+            path: self.path,
+            node: node.as_deref().map(String::as_str),
+            retries,
+            status,
+            status_class,
+            latency: self.started.elapsed(),
+        });
+    }
+}
+
+impl Drop for Metered<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.report(None, StatusClass::Cancelled);
+        }
+    }
+}
+
+/// A [`MetricsRecorder`] reporting to the [`metrics`](https://docs.rs/metrics) crate.
+///
+/// It reports:
+/// - `typesense_requests_in_flight`, a gauge labeled with the `endpoint`,
+/// - `typesense_requests_total`, a counter labeled with the `endpoint`,
+///   the `status` class and the `node`,
+/// - `typesense_request_errors_total`, the same counter for the requests
+///   that did not succeed,
+/// - `typesense_request_duration_seconds`, a histogram labeled with
+///   the `endpoint`, the `collection` and the `status` class.
+#[cfg(feature = "metrics")]
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsCrateRecorder;
+
+#[cfg(feature = "metrics")]
+impl MetricsRecorder for MetricsCrateRecorder {
+    fn on_request_start(&self, request: &RequestStart<'_>) {
+        ::metrics::gauge!("typesense_requests_in_flight", "endpoint" => request.endpoint.as_str())
+            .increment(1.0);
+    }
+
+    fn on_request_finish(&self, request: &RequestFinish<'_>) {
+        let endpoint = request.endpoint.as_str();
+        let status = request.status_class.as_str();
+        let node = request.node.unwrap_or_default().to_string();
+
+        ::metrics::gauge!("typesense_requests_in_flight", "endpoint" => endpoint).decrement(1.0);
+        ::metrics::counter!(
+            "typesense_requests_total",
+            "endpoint" => endpoint,
+            "status" => status,
+            "node" => node.clone(),
+        )
+        .increment(1);
+        if request.status_class != StatusClass::Success {
+            ::metrics::counter!(
+                "typesense_request_errors_total",
+                "endpoint" => endpoint,
+                "status" => status,
+                "node" => node,
+            )
+            .increment(1);
+        }
+        ::metrics::histogram!(
+            "typesense_request_duration_seconds",
+            "endpoint" => endpoint,
+            "collection" => request.collection.unwrap_or_default().to_string(),
+            "status" => status,
+        )
+        .record(request.latency.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_endpoints() {
+        let classify = Endpoint::classify;
+
+        assert_eq!(
+            classify("/collections/companies/documents/search?q=stark"),
+            (Endpoint::Search, Some("companies"))
+        );
+        assert_eq!(classify("/multi_search"), (Endpoint::Search, None));
+        assert_eq!(
+            classify("/collections/companies/documents/import?action=upsert"),
+            (Endpoint::Import, Some("companies"))
+        );
+        assert_eq!(
+            classify("/collections/companies/documents/42"),
+            (Endpoint::Documents, Some("companies"))
+        );
+        assert_eq!(
+            classify("/collections/companies"),
+            (Endpoint::Collection, Some("companies"))
+        );
+        assert_eq!(classify("/collections"), (Endpoint::Collection, None));
+        assert_eq!(
+            classify("/collections/companies/synonyms/nyc"),
+            (Endpoint::Other, Some("companies"))
+        );
+        assert_eq!(classify("/keys/1"), (Endpoint::Keys, None));
+        assert_eq!(classify("/health"), (Endpoint::Health, None));
+    }
+
+    #[test]
+    fn dropped_requests_are_cancelled() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<StatusClass>>);
+
+        impl MetricsRecorder for Recorder {
+            fn on_request_finish(&self, request: &RequestFinish<'_>) {
+                self.0.lock().unwrap().push(request.status_class);
+            }
+        }
+
+        let recorder = Recorder::default();
+        let finished = Metered::start(Some(&recorder), &Method::GET, "/keys");
+        finished.finish(&Ok(Response::new(())));
+        drop(Metered::start(Some(&recorder), &Method::GET, "/keys"));
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [StatusClass::Success, StatusClass::Cancelled]
+        );
+    }
+}
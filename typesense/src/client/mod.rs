@@ -1,26 +1,61 @@
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
+use futures_timer::Delay;
+use futures_util::future::{self, Either};
 use http::Response;
+use instant::Instant;
+use typesense_codegen::models::HealthStatus;
 
 use crate::collection::CollectionClient;
-use crate::transport::HttpLowLevel;
-use crate::transport::Transport;
+use crate::transport::{is_idempotent, Transport};
+use crate::transport::{BodyStream, HttpLowLevel, HttpLowLevelStream};
 use crate::Result;
 
 mod builder;
+mod cache;
+mod coalesce;
+mod config;
+mod hedge;
 pub mod keys;
+mod limit;
+pub mod metrics;
+mod node;
+mod trace;
 
 pub use builder::ClientBuilder;
+pub use config::{ClientConfig, RetryConfig};
+pub use hedge::HedgePolicy;
 pub use keys::ClientKeys;
+pub use limit::RequestLimit;
+pub use node::NodeHealth;
+
+use self::metrics::{Endpoint, Metered, MetricsRecorder};
+use cache::SearchCache;
+use coalesce::Coalescer;
+use hedge::{HedgedRequest, Hedging};
+use limit::Traffic;
+use node::{Node, Nodes};
 
 pub const TYPESENSE_API_KEY_HEADER_NAME: &str = "X-TYPESENSE-API-KEY";
 
+/// The query parameter of the api key, when it is not sent as a header.
+/// See [`ClientBuilder::api_key_in_query`].
+pub const TYPESENSE_API_KEY_QUERY_PARAM: &str = "x-typesense-api-key";
+
 /// Root client for top level APIs
 #[derive(Clone)]
 pub struct Client<T> {
     transport: Transport<T>,
-    host: Arc<String>,
-    api_key: Arc<String>,
+    nodes: Arc<Nodes>,
+    headers: http::HeaderMap,
+    api_key_param: Option<String>,
+    timeout: Option<Duration>,
+    metrics: Option<Arc<dyn MetricsRecorder>>,
+    search_cache: Option<Arc<SearchCache>>,
+    coalescer: Option<Arc<Coalescer>>,
+    hedging: Option<Arc<Hedging>>,
 }
 
 impl<T> Client<T> {
@@ -28,6 +63,56 @@ impl<T> Client<T> {
     pub fn transport(&self) -> &Transport<T> {
         &self.transport
     }
+
+    /// Gets the current health of the nodes of the client,
+    /// starting with the nearest node, if any.
+    pub fn nodes_health(&self) -> Vec<NodeHealth> {
+        self.nodes.health()
+    }
+
+    /// Drop the cached results of the searches in a collection.
+    ///
+    /// The writes sent through the client already do so; this is needed
+    /// when the collection is written to by other means, or through an alias.
+    /// See [`ClientBuilder::search_cache`].
+    pub fn invalidate_search_cache(&self, collection_name: &str) {
+        if let Some(cache) = &self.search_cache {
+            cache.invalidate(collection_name);
+        }
+    }
+
+    /// Drop the cached results of the searches in the collection
+    /// written to by a request, if any.
+    fn invalidate_written(&self, method: &http::Method, path: &str) {
+        if Traffic::of(method, path) != Some(Traffic::Write) {
+            return;
+        }
+        if let (_, Some(collection)) = Endpoint::classify(path) {
+            self.invalidate_search_cache(collection);
+        }
+    }
+}
+
+impl<T> Client<T>
+where
+    T: Clone,
+{
+    /// Make a copy of the client whose requests use `timeout`
+    /// instead of the timeout of the transport.
+    ///
+    /// ```ignore
+    /// client
+    ///     .with_timeout(Duration::from_millis(200))
+    ///     .collection()
+    ///     .retrieve("companies")
+    ///     .await?;
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
 }
 
 impl<T> Client<T>
@@ -59,14 +144,293 @@ where
         path: &str,
         body: Vec<u8>,
     ) -> Result<Response<Vec<u8>>> {
-        let uri = format!("{}{}", self.host, path);
-        let mut headers = http::HeaderMap::default();
-        headers.insert(TYPESENSE_API_KEY_HEADER_NAME, self.api_key.parse().unwrap());
-        self.transport.send(method, &uri, headers, body).await
+        let metered = Metered::start(self.metrics.as_deref(), &method, path);
+        let traffic = Traffic::of(&method, path);
+        let send = |host| self.send_to_node(host, method.clone(), path, body.clone());
+        let send = match &self.hedging {
+            Some(hedging) if method == http::Method::GET || traffic == Some(Traffic::Search) => {
+                Either::Left(self.send_hedged(hedging, &metered, &method, traffic, send))
+            }
+            _ => Either::Right(async {
+                let round = self.nodes.next_round();
+                self.send_to_nodes(&metered, &method, traffic, &round, None, send)
+                    .await
+            }),
+        };
+
+        let result = trace::traced(&method, path, Some(body.len()), send).await;
+        metered.finish(&result);
+        self.invalidate_written(&method, path);
+        result
+    }
+
+    /// Same as `get`, but answered from the search cache, if enabled,
+    /// for the searches in `collection` that were already sent.
+    pub(crate) async fn get_cached(&self, collection: &str, path: &str) -> Result<Vec<u8>> {
+        let cache = match &self.search_cache {
+            Some(cache) => cache,
+            None => return Ok(self.get(path).await?.into_body()),
+        };
+
+        let generation = match cache.get(collection, path) {
+            (Some(body), _) => return Ok(body.to_vec()),
+            (None, generation) => generation,
+        };
+        let body = self.get(path).await?.into_body();
+        cache.insert(collection, path, &body, generation);
+
+        Ok(body)
+    }
+
+    /// Send a request to the nodes of `round` in turn with `send`, moving on
+    /// to the next node only if the current one could not serve the request.
+    ///
+    /// As a request that is not idempotent may have been applied by a node
+    /// that failed to answer, it is sent to the next node only if it could
+    /// not be sent at all.
+    ///
+    /// The request waits for the budget of its `traffic` on every node.
+    async fn send_to_nodes<F, Fut, R>(
+        &self,
+        metered: &Metered<'_>,
+        method: &http::Method,
+        traffic: Option<Traffic>,
+        round: &[(&Node, bool)],
+        hedged: Option<&HedgedRequest<'_>>,
+        send: F,
+    ) -> Result<R>
+    where
+        F: Fn(Arc<String>) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut last_error = None;
+        let mut retries = 0;
+
+        for &(node, probe) in round {
+            if let Some(hedged) = hedged {
+                if !hedged.try_node(&node.host) {
+                    continue;
+                }
+            }
+            if probe {
+                if let Err(e) = self.probe(node.host.clone()).await {
+                    node.set_healthy(false);
+                    last_error = Some(e);
+                    continue;
+                }
+            }
+
+            let permit = node.limiter.acquire(traffic).await;
+            metered.set_node(&node.host, retries);
+            let started = Instant::now();
+            let result = send(node.host.clone()).await;
+            drop(permit);
+
+            match result {
+                Err(e) if e.is_node_failure() => {
+                    node.set_healthy(false);
+                    if !is_idempotent(method) && !e.is_connect_failure() {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                    retries += 1;
+                }
+                result => {
+                    node.set_healthy(true);
+                    if let Some(hedged) = hedged {
+                        hedged.hedging.record(started.elapsed());
+                    }
+                    return result;
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e),
+            // Every node was already tried by the other send of a hedged
+            // request, which is still waiting for its response.
+            None => future::pending().await,
+        }
     }
 
+    /// Same as `send_to_nodes`, but sending the request again to the next
+    /// node if it has not been answered after the delay of `hedging`.
+    ///
+    /// The first response wins, and the other request is cancelled, unless
+    /// the hedged request fails because of its node. Neither request is sent
+    /// to a node the other one already tried.
+    async fn send_hedged<F, Fut, R>(
+        &self,
+        hedging: &Hedging,
+        metered: &Metered<'_>,
+        method: &http::Method,
+        traffic: Option<Traffic>,
+        send: F,
+    ) -> Result<R>
+    where
+        F: Fn(Arc<String>) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let round = self.nodes.next_round();
+        let request = HedgedRequest::new(hedging);
+        if round.len() < 2 {
+            return self
+                .send_to_nodes(metered, method, traffic, &round, Some(&request), send)
+                .await;
+        }
+
+        let delay = hedging.delay();
+        let first = self.send_to_nodes(metered, method, traffic, &round, Some(&request), &send);
+        let hedged = async {
+            Delay::new(delay).await;
+            self.send_to_nodes(metered, method, traffic, &round, Some(&request), &send)
+                .await
+        };
+        futures_util::pin_mut!(first, hedged);
+
+        match future::select(first, hedged).await {
+            Either::Right((Err(e), first)) if e.is_node_failure() => first.await,
+            Either::Left((result, _)) | Either::Right((result, _)) => result,
+        }
+    }
+
+    /// Same as `send`, but streaming the body of the request.
+    ///
+    /// As the body can't be replayed, the request is sent to a single node:
+    /// the first one that is healthy, or that passes its health check.
+    pub(crate) async fn send_body_stream(
+        &self,
+        method: http::Method,
+        path: &str,
+        body: BodyStream,
+    ) -> Result<Response<Vec<u8>>> {
+        let metered = Metered::start(self.metrics.as_deref(), &method, path);
+        let send = self.send_body_stream_to_node(&metered, method.clone(), path, body);
+
+        let result = trace::traced(&method, path, None, send).await;
+        metered.finish(&result);
+        self.invalidate_written(&method, path);
+        result
+    }
+
+    async fn send_body_stream_to_node(
+        &self,
+        metered: &Metered<'_>,
+        method: http::Method,
+        path: &str,
+        body: BodyStream,
+    ) -> Result<Response<Vec<u8>>> {
+        let round = self.nodes.next_round();
+        let mut nodes = round.iter().copied();
+
+        let node = loop {
+            let (node, probe) = nodes.next().expect("client has at least one node");
+
+            if probe {
+                if let Err(e) = self.probe(node.host.clone()).await {
+                    node.set_healthy(false);
+                    if nodes.len() == 0 {
+                        return Err(e);
+                    }
+                    continue;
+                }
+            }
+            break node;
+        };
+
+        let _permit = node.limiter.acquire(Traffic::of(&method, path)).await;
+        metered.set_node(&node.host, 0);
+        let uri = self.uri(&node.host, path);
+        let headers = self.headers();
+        let response = match self.timeout {
+            Some(timeout) => {
+                self.transport
+                    .send_body_stream_with_timeout(method.clone(), &uri, headers, body, timeout)
+                    .await
+            }
+            None => {
+                self.transport
+                    .send_body_stream(method.clone(), &uri, headers, body)
+                    .await
+            }
+        };
+
+        node.set_healthy(!matches!(&response, Err(e) if e.is_node_failure()));
+        response.map_err(|e| e.with_request(&method, path, &node.host))
+    }
+
+    /// Check that a node is healthy through the `/health` endpoint.
+    async fn probe(&self, host: Arc<String>) -> Result<()> {
+        let response = self
+            .send_to_node(host, http::Method::GET, "/health", Vec::new())
+            .await?;
+        let health: HealthStatus = serde_json::from_slice(response.body())?;
+
+        if health.ok {
+            Ok(())
+        } else {
+            Err(http::StatusCode::SERVICE_UNAVAILABLE.into())
+        }
+    }
+
+    async fn send_to_node(
+        &self,
+        host: Arc<String>,
+        method: http::Method,
+        path: &str,
+        body: Vec<u8>,
+    ) -> Result<Response<Vec<u8>>> {
+        let uri = self.uri(&host, path);
+        let headers = self.headers();
+
+        let response = match self.timeout {
+            Some(timeout) => {
+                self.transport
+                    .send_with_timeout(method.clone(), &uri, headers, body, timeout)
+                    .await
+            }
+            None => {
+                self.transport
+                    .send(method.clone(), &uri, headers, body)
+                    .await
+            }
+        };
+
+        response.map_err(|e| e.with_request(&method, path, &host))
+    }
+
+    /// The uri of `path` on `host`, with the api key
+    /// as query parameter if it is not sent as a header.
+    fn uri(&self, host: &str, path: &str) -> String {
+        match &self.api_key_param {
+            Some(param) if path.contains('?') => format!("{}{}&{}", host, path, param),
+            Some(param) => format!("{}{}?{}", host, path, param),
+            None => format!("{}{}", host, path),
+        }
+    }
+
+    fn headers(&self) -> http::HeaderMap {
+        self.headers.clone()
+    }
+
+    /// Send a `GET` request, coalesced with the identical
+    /// ones in flight if enabled.
+    ///
+    /// Only the requests with the same timeout are identical, so that
+    /// none of them waits for longer than its own timeout.
     pub(crate) async fn get(&self, path: &str) -> Result<Response<Vec<u8>>> {
-        self.send(http::Method::GET, path, Vec::new()).await
+        let send = || self.send(http::Method::GET, path, Vec::new());
+
+        match &self.coalescer {
+            Some(coalescer) => {
+                let key = match self.timeout {
+                    Some(timeout) => format!("{} {}", timeout.as_nanos(), path),
+                    None => path.to_string(),
+                };
+                coalescer.coalesce(&key, send).await
+            }
+            None => send().await,
+        }
     }
 
     pub(crate) async fn post(&self, path: &str, body: Vec<u8>) -> Result<Response<Vec<u8>>> {
@@ -78,6 +442,406 @@ where
     }
 }
 
+impl<C> Client<C>
+where
+    C: HttpLowLevelStream,
+{
+    /// Same as `send`, but streaming the body of the response.
+    pub(crate) async fn send_streaming(
+        &self,
+        method: http::Method,
+        path: &str,
+        body: Vec<u8>,
+    ) -> Result<Response<BodyStream>> {
+        let metered = Metered::start(self.metrics.as_deref(), &method, path);
+        let traffic = Traffic::of(&method, path);
+        let round = self.nodes.next_round();
+        let send = self.send_to_nodes(&metered, &method, traffic, &round, None, |host| {
+            let uri = self.uri(&host, path);
+            let headers = self.headers();
+            let method = &method;
+            let body = body.clone();
+
+            async move {
+                let response = match self.timeout {
+                    Some(timeout) => {
+                        self.transport
+                            .send_streaming_with_timeout(
+                                method.clone(),
+                                &uri,
+                                headers,
+                                body,
+                                timeout,
+                            )
+                            .await
+                    }
+                    None => {
+                        self.transport
+                            .send_streaming(method.clone(), &uri, headers, body)
+                            .await
+                    }
+                };
+
+                response.map_err(|e| e.with_request(method, path, &host))
+            }
+        });
+
+        let result = trace::traced(&method, path, Some(body.len()), send).await;
+        metered.finish(&result);
+        result
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod node_tests {
+    use http::{Method, StatusCode};
+
+    use super::*;
+    use crate::transport::{MockTransport, TransportBuilder};
+    use crate::TypesenseError;
+
+    fn client(mock: &MockTransport) -> Client<MockTransport> {
+        ClientBuilder::default()
+            .transport(TransportBuilder::new(mock.clone()).build())
+            .nodes(["http://a", "http://b"])
+            .api_key("VerySecretKey")
+            .build()
+            .unwrap()
+    }
+
+    fn hosts(mock: &MockTransport) -> Vec<String> {
+        mock.requests().into_iter().map(|r| r.uri).collect()
+    }
+
+    #[tokio::test]
+    async fn fails_over_to_next_node() {
+        let mock = MockTransport::new();
+        mock.respond_once(Method::GET, "/keys", StatusCode::SERVICE_UNAVAILABLE, "")
+            .respond(Method::GET, "/keys", StatusCode::OK, "");
+        let client = client(&mock);
+
+        client.get("/keys").await.unwrap();
+
+        assert_eq!(hosts(&mock), ["http://a/keys", "http://b/keys"]);
+        assert!(!client.nodes_health()[0].healthy);
+        assert_eq!(
+            mock.requests()[0].headers[TYPESENSE_API_KEY_HEADER_NAME],
+            "VerySecretKey"
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_default_headers() {
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::OK, "")
+            .respond(Method::GET, "/collections", StatusCode::OK, "");
+        let builder = || {
+            ClientBuilder::default()
+                .transport(TransportBuilder::new(mock.clone()).build())
+                .host("http://a")
+                .api_key("Very Secret&Key")
+                .default_header("X-Request-Source", "tests")
+                .user_agent("my-app/1.0")
+        };
+
+        builder().build().unwrap().get("/keys").await.unwrap();
+        let client = builder().api_key_in_query(true).build().unwrap();
+        client.get("/keys").await.unwrap();
+        client.get("/collections?limit=1").await.unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].uri, "http://a/keys");
+        assert_eq!(
+            requests[0].headers[TYPESENSE_API_KEY_HEADER_NAME],
+            "Very Secret&Key"
+        );
+        assert_eq!(
+            requests[1].uri,
+            "http://a/keys?x-typesense-api-key=Very+Secret%26Key"
+        );
+        assert_eq!(
+            requests[2].uri,
+            "http://a/collections?limit=1&x-typesense-api-key=Very+Secret%26Key"
+        );
+        for request in &requests {
+            assert_eq!(request.headers["x-request-source"], "tests");
+            assert_eq!(request.headers[http::header::USER_AGENT], "my-app/1.0");
+        }
+        assert!(!requests[1]
+            .headers
+            .contains_key(TYPESENSE_API_KEY_HEADER_NAME));
+    }
+
+    #[tokio::test]
+    async fn does_not_fail_over_on_client_errors() {
+        let mock = MockTransport::new();
+        let client = client(&mock);
+
+        let response = client.get("/keys/1").await;
+
+        let error = response.unwrap_err();
+        assert!(matches!(error, TypesenseError::ObjectNotFound(_)));
+        let error = error.api_error().unwrap();
+        assert_eq!(error.method, Some(Method::GET));
+        assert_eq!(error.path.as_deref(), Some("/keys/1"));
+        assert_eq!(error.node.as_deref(), Some("http://a"));
+        assert_eq!(hosts(&mock), ["http://a/keys/1"]);
+    }
+
+    #[tokio::test]
+    async fn does_not_fail_over_writes_sent_to_a_node() {
+        let mock = MockTransport::new();
+        mock.respond(Method::POST, "/keys", StatusCode::SERVICE_UNAVAILABLE, "");
+        let client = client(&mock);
+
+        let error = client.post("/keys", Vec::new()).await.unwrap_err();
+
+        assert!(matches!(error, TypesenseError::ServiceUnavailable(_)));
+        assert_eq!(hosts(&mock), ["http://a/keys"]);
+        assert!(!client.nodes_health()[0].healthy);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn futures_can_be_spawned() {
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::OK, r#"{"keys":[]}"#);
+        let client = client(&mock);
+
+        let keys = tokio::spawn({
+            let client = client.clone();
+            async move { client.keys().retrieve_all().await }
+        });
+        let collection = tokio::spawn(async move {
+            let body = futures_util::stream::once(async { Ok(bytes::Bytes::from("{}")) });
+            client
+                .collection()
+                .import_documents("companies", Box::pin(body), None)
+                .await
+        });
+
+        assert!(keys.await.unwrap().unwrap().keys.is_empty());
+        assert!(matches!(
+            collection.await.unwrap(),
+            Err(TypesenseError::ObjectNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn probes_unhealthy_nodes() {
+        let mock = MockTransport::new();
+        mock.respond_once(Method::GET, "/keys", StatusCode::SERVICE_UNAVAILABLE, "")
+            .respond(Method::GET, "/keys", StatusCode::OK, "")
+            .respond(Method::GET, "/health", StatusCode::OK, r#"{"ok":true}"#);
+        let client = ClientBuilder::default()
+            .transport(TransportBuilder::new(mock.clone()).build())
+            .host("http://a")
+            .healthcheck_interval(Duration::from_secs(0))
+            .api_key("VerySecretKey")
+            .build()
+            .unwrap();
+
+        assert!(client.get("/keys").await.is_err());
+        assert!(!client.nodes_health()[0].healthy);
+
+        client.get("/keys").await.unwrap();
+
+        assert_eq!(
+            hosts(&mock),
+            ["http://a/keys", "http://a/health", "http://a/keys"]
+        );
+        assert!(client.nodes_health()[0].healthy);
+    }
+
+    #[tokio::test]
+    async fn records_metrics() {
+        use std::sync::Mutex;
+
+        use super::metrics::{Endpoint, RequestFinish, RequestStart};
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl MetricsRecorder for Recorder {
+            fn on_request_start(&self, request: &RequestStart<'_>) {
+                assert_eq!(request.endpoint, Endpoint::Search);
+                self.0.lock().unwrap().push("start".to_string());
+            }
+
+            fn on_request_finish(&self, request: &RequestFinish<'_>) {
+                assert_eq!(request.endpoint, Endpoint::Search);
+                assert_eq!(request.collection, Some("companies"));
+                self.0.lock().unwrap().push(format!(
+                    "{} {} {:?} {}",
+                    request.node.unwrap(),
+                    request.retries,
+                    request.status_class,
+                    request.status.unwrap().as_u16(),
+                ));
+            }
+        }
+
+        let mock = MockTransport::new();
+        let path = "/collections/companies/documents/search";
+        mock.respond_once(Method::GET, path, StatusCode::SERVICE_UNAVAILABLE, "")
+            .respond(Method::GET, path, StatusCode::OK, "{}");
+        let recorder = Arc::new(Recorder::default());
+        let client = ClientBuilder::default()
+            .transport(TransportBuilder::new(mock.clone()).build())
+            .nodes(["http://a", "http://b"])
+            .api_key("VerySecretKey")
+            .metrics_recorder(recorder.clone())
+            .build()
+            .unwrap();
+
+        client.get(&format!("{}?q=stark", path)).await.unwrap();
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            ["start", "http://b 1 Success 200"]
+        );
+    }
+
+    #[tokio::test]
+    async fn caches_searches_until_written() {
+        use typesense_codegen::models::SearchParameters;
+
+        let mock = MockTransport::new();
+        let search = "/collections/companies/documents/search";
+        let import = "/collections/companies/documents/import";
+        mock.respond(Method::GET, search, StatusCode::OK, r#"{"found":1}"#)
+            .respond(Method::POST, import, StatusCode::OK, "");
+        let client = ClientBuilder::default()
+            .transport(TransportBuilder::new(mock.clone()).build())
+            .host("http://a")
+            .api_key("VerySecretKey")
+            .search_cache(16, Duration::from_secs(60))
+            .build()
+            .unwrap();
+        let parameters = SearchParameters::new("stark".to_string(), "name".to_string());
+        let collection = client.collection();
+
+        let result = collection.search("companies", &parameters).await.unwrap();
+        assert_eq!(result.found, Some(1));
+        collection.search("companies", &parameters).await.unwrap();
+        assert_eq!(hosts(&mock).len(), 1);
+        assert_eq!(
+            hosts(&mock)[0],
+            "http://a/collections/companies/documents/search?q=stark&query_by=name"
+        );
+
+        let documents = futures_util::stream::empty();
+        collection
+            .import_documents("companies", Box::pin(documents), None)
+            .await
+            .unwrap();
+        collection.search("companies", &parameters).await.unwrap();
+        assert_eq!(hosts(&mock).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn coalesces_requests_with_same_timeout() {
+        /// Answers the requests after a while, so that they are in flight together.
+        #[derive(Clone)]
+        struct Slow(MockTransport);
+
+        #[async_trait::async_trait]
+        impl HttpLowLevel for Slow {
+            async fn send(
+                &self,
+                method: Method,
+                uri: &str,
+                headers: http::HeaderMap,
+                body: Vec<u8>,
+            ) -> Result<Response<Vec<u8>>> {
+                Delay::new(Duration::from_millis(10)).await;
+                self.0.send(method, uri, headers, body).await
+            }
+        }
+
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::OK, "{}");
+        let client = ClientBuilder::default()
+            .transport(TransportBuilder::new(Slow(mock.clone())).build())
+            .host("http://a")
+            .api_key("VerySecretKey")
+            .coalesce_requests(true)
+            .build()
+            .unwrap();
+        let fast = client.with_timeout(Duration::from_millis(200));
+
+        let (first, second) = future::join(client.get("/keys"), client.get("/keys")).await;
+        assert_eq!(first.unwrap().body(), b"{}");
+        assert_eq!(second.unwrap().body(), b"{}");
+        assert_eq!(hosts(&mock).len(), 1);
+
+        let (first, second) = future::join(client.get("/keys"), fast.get("/keys")).await;
+        assert_eq!(first.unwrap().body(), b"{}");
+        assert_eq!(second.unwrap().body(), b"{}");
+        assert_eq!(hosts(&mock).len(), 3);
+    }
+
+    #[tokio::test]
+    async fn hedges_slow_requests() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        /// Fails the requests to `http://a`, and never answers those to
+        /// `http://b`, noting when they are cancelled.
+        struct Stalled {
+            mock: MockTransport,
+            cancelled: Arc<AtomicBool>,
+        }
+
+        struct Cancelled(Arc<AtomicBool>);
+
+        impl Drop for Cancelled {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl HttpLowLevel for Stalled {
+            async fn send(
+                &self,
+                method: Method,
+                uri: &str,
+                headers: http::HeaderMap,
+                body: Vec<u8>,
+            ) -> Result<Response<Vec<u8>>> {
+                if uri.starts_with("http://a") {
+                    return Err(TypesenseError::Timeout);
+                }
+                if uri.starts_with("http://b") {
+                    let _cancelled = Cancelled(self.cancelled.clone());
+                    futures_util::future::pending::<()>().await;
+                }
+                self.mock.send(method, uri, headers, body).await
+            }
+        }
+
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::OK, "{}");
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let stalled = Stalled {
+            mock: mock.clone(),
+            cancelled: cancelled.clone(),
+        };
+        let client = ClientBuilder::default()
+            .transport(TransportBuilder::new(stalled).build())
+            .nodes(["http://a", "http://b", "http://c"])
+            .api_key("VerySecretKey")
+            .retry_policy(crate::transport::RetryPolicy::new().max_attempts(1))
+            .hedge_requests(HedgePolicy::new().initial_delay(Duration::from_millis(10)))
+            .build()
+            .unwrap();
+
+        let response = client.get("/keys").await.unwrap();
+        assert_eq!(response.body(), b"{}");
+        assert_eq!(hosts(&mock), ["http://c/keys"]);
+        assert!(cancelled.load(Ordering::SeqCst));
+    }
+}
+
 #[cfg(all(test, feature = "tokio-rt", not(target_arch = "wasm32")))]
 mod hyper_tests {
     use http::StatusCode;
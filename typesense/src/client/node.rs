@@ -0,0 +1,312 @@
+use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use instant::Instant;
+
+use super::limit::{Limits, NodeLimiter};
+
+/// The set of Typesense nodes a [`Client`](super::Client) sends requests to.
+///
+/// The `nearest_node`, when configured, is always tried first. The remaining
+/// nodes are tried in a round-robin fashion, every request starting one node
+/// further than the previous one.
+///
+/// A node that failed is skipped until the `healthcheck_interval` elapsed,
+/// it is then probed again before being used, by a single request at once.
+pub(crate) struct Nodes {
+    nearest_node: Option<Node>,
+    nodes: Vec<Node>,
+    current: AtomicUsize,
+    healthcheck_interval: Duration,
+}
+
+/// A Typesense node along with its health and the budgets of its requests.
+pub(crate) struct Node {
+    pub(crate) host: Arc<String>,
+    pub(crate) limiter: NodeLimiter,
+    health: Mutex<Health>,
+}
+
+#[derive(Clone, Copy)]
+struct Health {
+    healthy: bool,
+    checked_at: Instant,
+    /// Whether the node was handed out to a request to be probed.
+    probing: bool,
+}
+
+/// The nodes a request is sent to, in the order they should be tried,
+/// along with whether they have to be probed before being used.
+///
+/// The nodes to probe that the request did not probe are handed out
+/// again when it is dropped.
+pub(crate) struct Round<'a>(Vec<(&'a Node, bool)>);
+
+/// The health of a node, as last observed by the [`Client`](super::Client).
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeHealth {
+    /// The host of the node.
+    pub host: String,
+    /// Whether the node answered the last request sent to it.
+    pub healthy: bool,
+}
+
+impl Nodes {
+    pub(crate) fn new(
+        nearest_node: Option<Arc<String>>,
+        nodes: Vec<Arc<String>>,
+        healthcheck_interval: Duration,
+        limits: &Limits,
+    ) -> Self {
+        let node = |host| Node::new(host, limits);
+        // The nearest node is tried first, not once more among the others.
+        let nodes = nodes
+            .into_iter()
+            .filter(|host| Some(host) != nearest_node.as_ref())
+            .map(node)
+            .collect();
+
+        Self {
+            nearest_node: nearest_node.map(node),
+            nodes,
+            current: AtomicUsize::new(0),
+            healthcheck_interval,
+        }
+    }
+
+    /// The nodes in the order they should be tried for the next request.
+    ///
+    /// If every node is unhealthy, all of them are returned anyway.
+    pub(crate) fn next_round(&self) -> Round<'_> {
+        let len = self.nodes.len();
+        let start = if len > 0 {
+            self.current.fetch_add(1, Ordering::Relaxed) % len
+        } else {
+            0
+        };
+
+        let round = || {
+            self.nearest_node
+                .iter()
+                .chain(self.nodes[start..].iter())
+                .chain(self.nodes[..start].iter())
+        };
+
+        let now = Instant::now();
+        let available: Vec<_> = round()
+            .filter_map(|node| node.availability(now, self.healthcheck_interval))
+            .collect();
+
+        if available.is_empty() {
+            Round(round().map(|node| (node, false)).collect())
+        } else {
+            Round(available)
+        }
+    }
+
+    pub(crate) fn health(&self) -> Vec<NodeHealth> {
+        self.nearest_node
+            .iter()
+            .chain(self.nodes.iter())
+            .map(|node| NodeHealth {
+                host: node.host.to_string(),
+                healthy: node.health.lock().unwrap().healthy,
+            })
+            .collect()
+    }
+}
+
+impl<'a> Deref for Round<'a> {
+    type Target = [(&'a Node, bool)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for Round<'_> {
+    fn drop(&mut self) {
+        for (node, probe) in &self.0 {
+            if *probe {
+                if let Ok(mut health) = node.health.lock() {
+                    health.probing = false;
+                }
+            }
+        }
+    }
+}
+
+impl Node {
+    fn new(host: Arc<String>, limits: &Limits) -> Self {
+        Self {
+            host,
+            limiter: NodeLimiter::new(limits),
+            health: Mutex::new(Health {
+                healthy: true,
+                checked_at: Instant::now(),
+                probing: false,
+            }),
+        }
+    }
+
+    /// Whether the node can be used, and if it has to be probed first.
+    ///
+    /// An unhealthy node is returned once its `interval` elapsed,
+    /// to a single request at once so concurrent requests don't all
+    /// probe it.
+    fn availability(&self, now: Instant, interval: Duration) -> Option<(&Self, bool)> {
+        let mut health = self.health.lock().unwrap();
+
+        if health.healthy {
+            Some((self, false))
+        } else if !health.probing && now.duration_since(health.checked_at) >= interval {
+            health.probing = true;
+            Some((self, true))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn set_healthy(&self, healthy: bool) {
+        *self.health.lock().unwrap() = Health {
+            healthy,
+            checked_at: Instant::now(),
+            probing: false,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(host: &str) -> Arc<String> {
+        Arc::new(host.to_string())
+    }
+
+    fn round(nodes: &Nodes) -> Vec<(&str, bool)> {
+        nodes
+            .next_round()
+            .iter()
+            .map(|&(n, probe)| (n.host.as_str(), probe))
+            .collect()
+    }
+
+    #[test]
+    fn rotates_through_nodes() {
+        let nodes = Nodes::new(
+            None,
+            vec![node("a"), node("b"), node("c")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+
+        assert_eq!(round(&nodes), [("a", false), ("b", false), ("c", false)]);
+        assert_eq!(round(&nodes), [("b", false), ("c", false), ("a", false)]);
+        assert_eq!(round(&nodes), [("c", false), ("a", false), ("b", false)]);
+        assert_eq!(round(&nodes), [("a", false), ("b", false), ("c", false)]);
+    }
+
+    #[test]
+    fn nearest_node_is_tried_first() {
+        let nodes = Nodes::new(
+            Some(node("near")),
+            vec![node("a"), node("b")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+
+        assert_eq!(round(&nodes), [("near", false), ("a", false), ("b", false)]);
+        assert_eq!(round(&nodes), [("near", false), ("b", false), ("a", false)]);
+
+        let nodes = Nodes::new(
+            Some(node("near")),
+            vec![],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+
+        assert_eq!(round(&nodes), [("near", false)]);
+
+        let nodes = Nodes::new(
+            Some(node("a")),
+            vec![node("a"), node("b")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+
+        assert_eq!(round(&nodes), [("a", false), ("b", false)]);
+    }
+
+    #[test]
+    fn unhealthy_nodes_are_skipped_until_interval_elapsed() {
+        let nodes = Nodes::new(
+            None,
+            vec![node("a"), node("b")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+        nodes.nodes[0].set_healthy(false);
+
+        assert_eq!(round(&nodes), [("b", false)]);
+        assert_eq!(round(&nodes), [("b", false)]);
+        assert_eq!(
+            nodes.health(),
+            [
+                NodeHealth {
+                    host: "a".to_string(),
+                    healthy: false
+                },
+                NodeHealth {
+                    host: "b".to_string(),
+                    healthy: true
+                },
+            ]
+        );
+
+        let nodes = Nodes::new(
+            None,
+            vec![node("a"), node("b")],
+            Duration::from_secs(0),
+            &Limits::default(),
+        );
+        nodes.nodes[0].set_healthy(false);
+
+        assert_eq!(round(&nodes), [("a", true), ("b", false)]);
+    }
+
+    #[test]
+    fn nodes_not_probed_are_handed_out_again() {
+        let nodes = Nodes::new(
+            None,
+            vec![node("a"), node("b")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+        nodes.nodes[0].set_healthy(false);
+        nodes.nodes[0].health.lock().unwrap().checked_at -= Duration::from_secs(61);
+
+        let first = nodes.next_round();
+        assert_eq!(round(&nodes), [("b", false)]);
+        assert!(first[0].1);
+        drop(first);
+
+        assert_eq!(round(&nodes), [("a", true), ("b", false)]);
+    }
+
+    #[test]
+    fn every_node_is_tried_when_all_are_unhealthy() {
+        let nodes = Nodes::new(
+            None,
+            vec![node("a"), node("b")],
+            Duration::from_secs(60),
+            &Limits::default(),
+        );
+        nodes.nodes[0].set_healthy(false);
+        nodes.nodes[1].set_healthy(false);
+
+        assert_eq!(round(&nodes), [("a", false), ("b", false)]);
+    }
+}
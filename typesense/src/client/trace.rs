@@ -0,0 +1,180 @@
+//! The spans of the requests sent by the [`Client`](super::Client),
+//! when the `tracing` feature is enabled.
+//!
+//! Only the method, the path, the node and the sizes of the requests are
+//! recorded: neither the headers, holding the API key, nor the bodies are.
+//!
+//! The `retries` of a request count the nodes it failed on, and its
+//! `attempts` count the attempts on its last node, retries of the
+//! [`RetryPolicy`](crate::transport::RetryPolicy) included.
+
+use std::future::Future;
+
+use http::Response;
+
+use crate::Result;
+
+/// Send a request with `send`, within a span describing it.
+#[cfg(feature = "tracing")]
+pub(crate) async fn traced<F, B>(
+    method: &http::Method,
+    path: &str,
+    request_size: Option<usize>,
+    send: F,
+) -> Result<Response<B>>
+where
+    F: Future<Output = Result<Response<B>>>,
+    B: BodySize,
+{
+    use tracing::field::Empty;
+    use tracing::Instrument;
+
+    let span = tracing::info_span!(
+        "typesense.request",
+        method = %method,
+        path = %path_template(path),
+        node = Empty,
+        status = Empty,
+        error = Empty,
+        latency_ms = Empty,
+        request_size,
+        response_size = Empty,
+        retries = 0,
+        attempts = 1,
+    );
+    let started = instant::Instant::now();
+
+    let result = send.instrument(span.clone()).await;
+
+    span.record("latency_ms", started.elapsed().as_millis() as u64);
+    match &result {
+        Ok(response) => {
+            span.record("status", response.status().as_u16());
+            if let Some(size) = response.body().size() {
+                span.record("response_size", size);
+            }
+        }
+        Err(error) => {
+            if let Some(api_error) = error.api_error() {
+                span.record("status", api_error.status.as_u16());
+            }
+            span.record("error", tracing::field::display(error));
+        }
+    }
+
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn traced<F, B>(
+    _method: &http::Method,
+    _path: &str,
+    _request_size: Option<usize>,
+    send: F,
+) -> Result<Response<B>>
+where
+    F: Future<Output = Result<Response<B>>>,
+{
+    send.await
+}
+
+/// Record the node the request is sent to, after `retries` failed attempts.
+#[cfg(feature = "tracing")]
+pub(crate) fn record_node(node: &str, retries: usize) {
+    let span = tracing::Span::current();
+    span.record("node", node);
+    span.record("retries", retries);
+    span.record("attempts", 1);
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn record_node(_node: &str, _retries: usize) {}
+
+/// The size of a response body, when it is known upfront.
+#[cfg(feature = "tracing")]
+pub(crate) trait BodySize {
+    fn size(&self) -> Option<usize>;
+}
+
+#[cfg(feature = "tracing")]
+impl BodySize for Vec<u8> {
+    fn size(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl BodySize for crate::transport::BodyStream {
+    fn size(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// The path of a request, with the names and ids of
+/// the resources replaced by placeholders, without the query.
+#[cfg(feature = "tracing")]
+fn path_template(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    let mut template = String::with_capacity(path.len());
+    let mut parent = "";
+
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        template.push('/');
+        let placeholder = match parent {
+            "collections" | "aliases" | "presets" | "stopwords" => Some("{name}"),
+            "documents" | "keys" | "synonyms" | "overrides" => Some("{id}"),
+            _ => None,
+        };
+
+        match placeholder {
+            Some(placeholder) if !is_action(segment) => {
+                template.push_str(placeholder);
+                parent = "";
+            }
+            _ => {
+                template.push_str(segment);
+                parent = segment;
+            }
+        }
+    }
+
+    if template.is_empty() {
+        template.push('/');
+    }
+    template
+}
+
+/// Whether a segment following a resource is an action on
+/// every resource, rather than the name or id of one of them.
+#[cfg(feature = "tracing")]
+fn is_action(segment: &str) -> bool {
+    matches!(segment, "search" | "import" | "export")
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_hide_names_and_ids() {
+        assert_eq!(path_template("/"), "/");
+        assert_eq!(path_template("/collections"), "/collections");
+        assert_eq!(
+            path_template("/collections/companies"),
+            "/collections/{name}"
+        );
+        assert_eq!(
+            path_template("/collections/companies/documents/search?q=stark"),
+            "/collections/{name}/documents/search"
+        );
+        assert_eq!(
+            path_template("/collections/companies/documents/42"),
+            "/collections/{name}/documents/{id}"
+        );
+        assert_eq!(
+            path_template("/collections/companies/documents/import?action=upsert"),
+            "/collections/{name}/documents/import"
+        );
+        assert_eq!(path_template("/keys/1"), "/keys/{id}");
+    }
+}
@@ -5,14 +5,41 @@
 //!
 
 use serde::{Deserialize, Serialize};
+use typesense_codegen::models::{SearchParameters, SearchResult};
 mod schema;
 pub use schema::{CollectionSchema, CollectionSchemaBuilder};
 
 use crate::client::Client;
 use crate::document::Document;
-use crate::transport::HttpLowLevel;
+use crate::transport::{BodyStream, HttpLowLevel, HttpLowLevelStream};
 use crate::Result;
 
+/// The action of an [import](CollectionClient::import_documents) of documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    /// Create the documents, failing for those that already exist.
+    Create,
+    /// Create the documents, replacing those that already exist.
+    Upsert,
+    /// Update the fields of the documents, failing for those that don't exist.
+    Update,
+    /// Update the fields of the documents, creating those that don't exist.
+    Emplace,
+}
+
+impl ImportAction {
+    /// The value of the `action` query parameter.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Upsert => "upsert",
+            Self::Update => "update",
+            Self::Emplace => "emplace",
+        }
+    }
+}
+
 /// Client for the Typesense CollectionAPI
 pub struct CollectionClient<T> {
     pub(crate) client: Client<T>,
@@ -63,6 +90,59 @@ where
         Ok(response)
     }
 
+    /// Import documents, formatted as JSON lines, into a collection.
+    ///
+    /// The documents are sent as they are produced by the stream, so they can
+    /// be read from a file or generated without holding them all in memory.
+    /// The `action` defaults to [`ImportAction::Create`].
+    ///
+    /// Returns the result of the import of every document, as JSON lines
+    /// in the same order as the documents.
+    pub async fn import_documents(
+        &self,
+        collection_name: &str,
+        documents: BodyStream,
+        action: impl Into<Option<ImportAction>>,
+    ) -> Result<String> {
+        let mut path = format!("/collections/{}/documents/import", collection_name);
+        if let Some(action) = action.into() {
+            path = format!("{}?action={}", path, action.as_str());
+        }
+
+        let response = self
+            .client
+            .send_body_stream(http::Method::POST, &path, documents)
+            .await?;
+
+        Ok(String::from_utf8_lossy(response.body()).into_owned())
+    }
+
+    /// Search for documents in a collection.
+    ///
+    /// When the [search cache](crate::ClientBuilder::search_cache) is
+    /// enabled, the result of a search with the same parameters may be
+    /// returned without sending it again.
+    ///
+    /// Returns a [`TypesenseError::ConfigError`](crate::TypesenseError::ConfigError)
+    /// if the parameters can't be sent as a query.
+    pub async fn search(
+        &self,
+        collection_name: &str,
+        parameters: &SearchParameters,
+    ) -> Result<SearchResult> {
+        let query = serde_urlencoded::to_string(parameters).map_err(|e| {
+            crate::TypesenseError::ConfigError(format!("invalid search parameters: {}", e))
+        })?;
+        let path = format!(
+            "/collections/{}/documents/search?{}",
+            collection_name, query
+        );
+
+        let response_body = self.client.get_cached(collection_name, &path).await?;
+
+        Ok(serde_json::from_slice(&response_body)?)
+    }
+
     /// Permanently drops a collection. This action cannot be undone.
     /// For large collections, this might have an impact on read latencies.
     pub async fn delete(&self, collection_name: &str) -> Result<CollectionResponse> {
@@ -75,6 +155,26 @@ where
     }
 }
 
+impl<T> CollectionClient<T>
+where
+    T: HttpLowLevelStream,
+{
+    /// Export all the documents of a collection, as JSON lines.
+    ///
+    /// The documents are streamed as they are received, so even
+    /// large collections can be exported without buffering them.
+    pub async fn export_documents(&self, collection_name: &str) -> Result<BodyStream> {
+        let path = format!("/collections/{}/documents/export", collection_name);
+
+        let response = self
+            .client
+            .send_streaming(http::Method::GET, &path, Vec::new())
+            .await?;
+
+        Ok(response.into_body())
+    }
+}
+
 /// Represents a Response from the Typesense Collection API.
 #[derive(Deserialize, Serialize)]
 pub struct CollectionResponse {
@@ -1,5 +1,8 @@
+use std::fmt;
+
 use http::StatusCode;
 use thiserror::Error;
+use typesense_codegen::models::ErrorResponse;
 
 /// [`Result`](std::result::Result) type that is returned from
 /// functions with error as [`TypesenseError`].
@@ -22,40 +25,45 @@ pub enum TypesenseError {
     Timeout,
 
     /// Request malformed.
-    #[error("request malformed")]
-    RequestMalformed,
+    #[error("request malformed: {0}")]
+    RequestMalformed(ApiError),
 
     /// Request unauthorized.
-    #[error("request unauthorized")]
-    RequestUnauthorized,
+    #[error("request unauthorized: {0}")]
+    RequestUnauthorized(ApiError),
 
     /// Request forbidden.
-    #[error("request forbidden")]
-    RequestForbidden,
+    #[error("request forbidden: {0}")]
+    RequestForbidden(ApiError),
 
     /// Object not found.
-    #[error("object not found")]
-    ObjectNotFound,
+    #[error("object not found: {0}")]
+    ObjectNotFound(ApiError),
 
     /// Object already exists.
-    #[error("object already exists")]
-    ObjectAlreadyExists,
+    #[error("object already exists: {0}")]
+    ObjectAlreadyExists(ApiError),
 
     /// Object unprocessable.
-    #[error("object unprocessable")]
-    ObjectUnprocessable,
+    #[error("object unprocessable: {0}")]
+    ObjectUnprocessable(ApiError),
 
-    /// Server error.
-    #[error("server error")]
-    ServerError,
+    /// Server error, for any 5xx status but 503.
+    #[error("server error: {0}")]
+    ServerError(ApiError),
 
     /// Service unavailable.
-    #[error("service unavailable")]
-    ServiceUnavailable,
+    #[error("service unavailable: {0}")]
+    ServiceUnavailable(ApiError),
+
+    /// HTTP status error, for the statuses without a dedicated variant.
+    #[error("HTTP status error: {0}")]
+    HttpStatusError(ApiError),
 
-    /// HTTP status error.
-    #[error("HTTP status error")]
-    HttpStatusError,
+    /// The circuit of the node is open, after too many requests
+    /// to it failed. See [`CircuitBreaker`](crate::transport::CircuitBreaker).
+    #[error("circuit open for {0}")]
+    CircuitOpen(String),
 
     /// HTTP error.
     #[error("http error: {0}")]
@@ -67,37 +75,224 @@ pub enum TypesenseError {
     #[error("hyper error: {0}")]
     HyperError(#[from] hyper::Error),
 
+    /// Reqwest error.
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+    #[error("reqwest error: {0}")]
+    ReqwestError(reqwest::Error),
+
+    /// Error of a [`TowerService`](crate::transport::TowerService).
+    #[cfg(feature = "tower")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+    #[error("tower service error: {0}")]
+    TowerError(Box<dyn std::error::Error + Send + Sync>),
+
     /// WASM error.
     #[cfg(target_arch = "wasm32")]
     #[cfg_attr(docsrs, doc(cfg(target_arch = "wasm32")))]
     #[error("wasm client error: {0:?}")]
     WasmError(String),
 
+    /// Request without a recorded response in the
+    /// [`Cassette`](crate::transport::Cassette) being replayed.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    #[error("no recorded response for {0}")]
+    UnrecordedRequest(String),
+
+    /// I/O error.
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+
     /// JSON error.
     #[error("json error: {0}")]
     JsonError(#[from] serde_json::Error),
 }
 
-impl From<StatusCode> for TypesenseError {
-    fn from(status: StatusCode) -> Self {
-        match status {
+/// The details of an error response sent by Typesense.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApiError {
+    /// The status of the response.
+    pub status: StatusCode,
+    /// The message explaining the error, sent in the body of the response.
+    pub message: Option<String>,
+    /// The method of the request.
+    pub method: Option<http::Method>,
+    /// The path of the request.
+    pub path: Option<String>,
+    /// The node the request was sent to.
+    pub node: Option<String>,
+}
+
+impl ApiError {
+    /// Create an error for a response with `status`, without any details.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            message: None,
+            method: None,
+            path: None,
+            node: None,
+        }
+    }
+
+    /// Create an error for a response with `status`, reading
+    /// the message from the [`ErrorResponse`] in its `body`.
+    pub(crate) fn from_response(status: StatusCode, body: &[u8]) -> Self {
+        Self {
+            message: serde_json::from_slice::<ErrorResponse>(body)
+                .ok()
+                .and_then(|response| response.message),
+            ..Self::new(status)
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let (Some(method), Some(path)) = (&self.method, &self.path) {
+            write!(f, " for {} {}", method, path)?;
+        }
+        if let Some(node) = &self.node {
+            write!(f, " on {}", node)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<ApiError> for TypesenseError {
+    fn from(error: ApiError) -> Self {
+        match error.status {
             // 400
-            StatusCode::BAD_REQUEST => Self::RequestMalformed,
+            StatusCode::BAD_REQUEST => Self::RequestMalformed(error),
             // 401
-            StatusCode::UNAUTHORIZED => Self::RequestUnauthorized,
+            StatusCode::UNAUTHORIZED => Self::RequestUnauthorized(error),
             // 403
-            StatusCode::FORBIDDEN => Self::RequestForbidden,
+            StatusCode::FORBIDDEN => Self::RequestForbidden(error),
             // 404
-            StatusCode::NOT_FOUND => Self::ObjectNotFound,
+            StatusCode::NOT_FOUND => Self::ObjectNotFound(error),
             // 409
-            StatusCode::CONFLICT => Self::ObjectAlreadyExists,
+            StatusCode::CONFLICT => Self::ObjectAlreadyExists(error),
             // 422
-            StatusCode::UNPROCESSABLE_ENTITY => Self::ObjectUnprocessable,
-            // 500
-            StatusCode::INTERNAL_SERVER_ERROR => Self::ServerError,
+            StatusCode::UNPROCESSABLE_ENTITY => Self::ObjectUnprocessable(error),
             // 503
-            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable,
-            _ => Self::TypesenseClientError,
+            StatusCode::SERVICE_UNAVAILABLE => Self::ServiceUnavailable(error),
+            // 500 and any other 5xx
+            status if status.is_server_error() => Self::ServerError(error),
+            _ => Self::HttpStatusError(error),
+        }
+    }
+}
+
+impl From<StatusCode> for TypesenseError {
+    fn from(status: StatusCode) -> Self {
+        ApiError::new(status).into()
+    }
+}
+
+impl TypesenseError {
+    /// Whether the error is caused by the node itself (it could not be reached
+    /// or answered with a 5xx), in which case the request can be sent to
+    /// another node.
+    pub(crate) fn is_node_failure(&self) -> bool {
+        match self {
+            Self::Timeout
+            | Self::ServerError(_)
+            | Self::ServiceUnavailable(_)
+            | Self::CircuitOpen(_) => true,
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::HyperError(_) => true,
+            #[cfg(feature = "reqwest")]
+            Self::ReqwestError(_) => true,
+            #[cfg(feature = "tower")]
+            Self::TowerError(e) => is_transport_error(&**e),
+            #[cfg(target_arch = "wasm32")]
+            Self::WasmError(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the request could not be sent to the node at all (its
+    /// connection failed, or its circuit is open), in which case even a
+    /// request that is not idempotent can be sent to another node.
+    pub(crate) fn is_connect_failure(&self) -> bool {
+        match self {
+            Self::CircuitOpen(_) => true,
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::HyperError(e) => e.is_connect(),
+            #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+            Self::ReqwestError(e) => e.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// The details of the error response sent by Typesense,
+    /// if the error was caused by one.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Self::RequestMalformed(error)
+            | Self::RequestUnauthorized(error)
+            | Self::RequestForbidden(error)
+            | Self::ObjectNotFound(error)
+            | Self::ObjectAlreadyExists(error)
+            | Self::ObjectUnprocessable(error)
+            | Self::ServerError(error)
+            | Self::ServiceUnavailable(error)
+            | Self::HttpStatusError(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Attach the request that caused the error, when it is an error response.
+    pub(crate) fn with_request(mut self, method: &http::Method, path: &str, node: &str) -> Self {
+        if let Some(error) = self.api_error_mut() {
+            error.method = Some(method.clone());
+            error.path = Some(path.to_string());
+            error.node = Some(node.to_string());
+        }
+        self
+    }
+
+    /// Copy the error, unless it wraps the error of a library
+    /// or of the platform, which can't be copied.
+    pub(crate) fn try_clone(&self) -> Option<Self> {
+        match self {
+            Self::TypesenseClientError => Some(Self::TypesenseClientError),
+            Self::ConfigError(message) => Some(Self::ConfigError(message.clone())),
+            Self::Timeout => Some(Self::Timeout),
+            Self::CircuitOpen(node) => Some(Self::CircuitOpen(node.clone())),
+            _ => self.api_error().map(|error| error.clone().into()),
+        }
+    }
+
+    fn api_error_mut(&mut self) -> Option<&mut ApiError> {
+        match self {
+            Self::RequestMalformed(error)
+            | Self::RequestUnauthorized(error)
+            | Self::RequestForbidden(error)
+            | Self::ObjectNotFound(error)
+            | Self::ObjectAlreadyExists(error)
+            | Self::ObjectUnprocessable(error)
+            | Self::ServerError(error)
+            | Self::ServiceUnavailable(error)
+            | Self::HttpStatusError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for TypesenseError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout
+        } else {
+            // The url may hold the api key, see `ClientBuilder::api_key_in_query`.
+            Self::ReqwestError(error.without_url())
         }
     }
 }
@@ -108,3 +303,77 @@ impl From<wasm_bindgen::JsValue> for TypesenseError {
         Self::WasmError(format!("{:?}", value))
     }
 }
+
+/// Whether the error of a tower service was caused by the connection
+/// to the node, rather than by one of its layers, like a load shed.
+#[cfg(feature = "tower")]
+fn is_transport_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    std::iter::successors(Some(error), |error| error.source()).any(|error| {
+        #[cfg(not(target_arch = "wasm32"))]
+        if error.is::<hyper::Error>() {
+            return true;
+        }
+        error.is::<std::io::Error>()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_responses_keep_their_details() {
+        let error: TypesenseError = ApiError::from_response(
+            StatusCode::BAD_REQUEST,
+            br#"{"message": "Could not parse the filter query."}"#,
+        )
+        .into();
+        let error = error.with_request(
+            &http::Method::GET,
+            "/collections/companies/documents/search",
+            "http://localhost:8108",
+        );
+
+        assert!(matches!(error, TypesenseError::RequestMalformed(_)));
+        assert_eq!(
+            error.to_string(),
+            "request malformed: 400 Bad Request for GET /collections/companies/documents/search \
+             on http://localhost:8108: Could not parse the filter query."
+        );
+    }
+
+    #[test]
+    fn unmapped_statuses_keep_their_code() {
+        let error = TypesenseError::from(ApiError::from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            b"not json",
+        ));
+
+        assert!(matches!(error, TypesenseError::HttpStatusError(_)));
+        assert_eq!(
+            error.api_error().unwrap().status,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(error.api_error().unwrap().message, None);
+        assert!(matches!(
+            TypesenseError::from(StatusCode::BAD_GATEWAY),
+            TypesenseError::ServerError(_)
+        ));
+    }
+
+    #[test]
+    fn only_errors_without_source_are_cloned() {
+        let error = TypesenseError::from(StatusCode::NOT_FOUND);
+        assert!(matches!(
+            error.try_clone(),
+            Some(TypesenseError::ObjectNotFound(api_error)) if api_error == *error.api_error().unwrap()
+        ));
+        assert!(matches!(
+            TypesenseError::Timeout.try_clone(),
+            Some(TypesenseError::Timeout)
+        ));
+
+        let error = TypesenseError::from(std::io::Error::other("reset"));
+        assert!(error.try_clone().is_none());
+    }
+}
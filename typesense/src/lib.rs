@@ -5,9 +5,15 @@
 //!
 //! Welcome to typesense, the rust library for the Typesense API.
 
-#[cfg(feature = "openapi_client")]
+#[cfg(any(feature = "openapi_client", feature = "openapi_client_rustls"))]
 pub use typesense_codegen as openapi_client;
 
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "blocking", not(target_arch = "wasm32"))))
+)]
+pub mod blocking;
 mod client;
 pub mod collection;
 pub mod document;
@@ -15,8 +21,11 @@ mod error;
 pub mod field;
 pub mod transport;
 
-pub use client::{keys, Client, ClientBuilder};
-pub use error::{Result, TypesenseError};
+pub use client::{
+    keys, metrics, Client, ClientBuilder, ClientConfig, HedgePolicy, NodeHealth, RequestLimit,
+    RetryConfig,
+};
+pub use error::{ApiError, Result, TypesenseError};
 
 #[cfg(feature = "typesense_derive")]
 #[doc(hidden)]
@@ -1,11 +1,26 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::circuit::Circuits;
 use super::http_low_level;
-use super::Transport;
+use super::{CircuitBreaker, Middleware, Middlewares, RetryPolicy, Transport};
 
 /// The [`TransportBuilder`] to build [`Transport`].
 ///
 /// Used to build [`Transport`] with custom configuration.
 pub struct TransportBuilder<C> {
     client: C,
+    retry_policy: Option<RetryPolicy>,
+    timeout: Option<Duration>,
+    middlewares: Middlewares,
+    circuit_breaker: Option<CircuitBreaker>,
+    /// Rebuilds the client with a connect timeout, for the built-in clients.
+    #[cfg(not(target_arch = "wasm32"))]
+    with_connect_timeout: Option<Box<dyn Fn(Duration) -> C + Send + Sync>>,
+    #[cfg(feature = "gzip")]
+    gzip_requests: bool,
+    #[cfg(feature = "gzip")]
+    gzip_responses: bool,
 }
 
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
@@ -17,10 +32,42 @@ impl TransportBuilder<http_low_level::HyperHttpsClient> {
     /// Used to make a new [`hyper`](https://docs.rs/hyper) client.
     /// The connector used is [`HttpsConnector`](hyper_tls::HttpsConnector).
     pub fn new_hyper() -> Self {
-        let https = http_low_level::HttpsConnector::new();
+        let https = http_low_level::https_connector(None);
         let client = hyper::Client::builder().build(https);
 
-        Self { client }
+        let mut builder = Self::new(client);
+        builder.with_connect_timeout = Some(Box::new(|connect_timeout| {
+            let https = http_low_level::https_connector(Some(connect_timeout));
+            hyper::Client::builder().build(https)
+        }));
+        builder
+    }
+}
+
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "rustls", not(target_arch = "wasm32")))))]
+impl TransportBuilder<http_low_level::HyperRustlsClient> {
+    /// Used to make a new [`hyper`](https://docs.rs/hyper) client using
+    /// [`rustls`](https://docs.rs/rustls), trusting the webpki roots.
+    pub fn new_hyper_rustls() -> Self {
+        Self::new_hyper_rustls_with_tls(super::TlsConfig::new())
+            .expect("default TLS configuration is valid")
+    }
+
+    /// Used to make a new [`hyper`](https://docs.rs/hyper) client using
+    /// [`rustls`](https://docs.rs/rustls), with a custom [`TlsConfig`](super::TlsConfig)
+    /// (CA bundles, client certificates).
+    pub fn new_hyper_rustls_with_tls(tls: super::TlsConfig) -> crate::Result<Self> {
+        let tls = tls.into_client_config()?;
+        let https = http_low_level::rustls_connector(tls.clone(), None);
+        let client = hyper::Client::builder().build(https);
+
+        let mut builder = Self::new(client);
+        builder.with_connect_timeout = Some(Box::new(move |connect_timeout| {
+            let https = http_low_level::rustls_connector(tls.clone(), Some(connect_timeout));
+            hyper::Client::builder().build(https)
+        }));
+        Ok(builder)
     }
 }
 
@@ -41,7 +88,27 @@ where
     {
         let client = hyper::Client::builder().executor(executor).build(connector);
 
-        Self { client }
+        Self::new(client)
+    }
+}
+
+#[cfg(feature = "reqwest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "reqwest")))]
+impl TransportBuilder<reqwest::Client> {
+    /// Used to make a client out of a [`reqwest`](https://docs.rs/reqwest) client,
+    /// to reuse its configuration (proxies, TLS, connection pool...).
+    pub fn new_reqwest(client: reqwest::Client) -> Self {
+        Self::new(client)
+    }
+}
+
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+impl<S> TransportBuilder<super::TowerService<S>> {
+    /// Used to make a client out of a [`tower`](https://docs.rs/tower) service,
+    /// to reuse its layers (timeouts, rate limits, load shedding...).
+    pub fn new_tower(service: S) -> Self {
+        Self::new(super::TowerService(service))
     }
 }
 
@@ -50,17 +117,115 @@ where
 impl TransportBuilder<http_low_level::WasmClient> {
     /// Used to make a new wasm client.
     pub fn new_wasm() -> Self {
-        Self {
-            client: http_low_level::WasmClient,
-        }
+        Self::new(http_low_level::WasmClient)
     }
 }
 
 impl<C> TransportBuilder<C> {
+    /// Used to make a builder from any [`HttpLowLevel`](super::HttpLowLevel) client.
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            retry_policy: None,
+            timeout: None,
+            middlewares: Middlewares::default(),
+            circuit_breaker: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            with_connect_timeout: None,
+            #[cfg(feature = "gzip")]
+            gzip_requests: false,
+            #[cfg(feature = "gzip")]
+            gzip_responses: false,
+        }
+    }
+
+    /// Set the [`RetryPolicy`] used to retry failed requests.
+    /// Without one, every request is attempted only once.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Set the timeout of every attempt of a request, from sending it to
+    /// receiving the whole response. Exceeding it fails the attempt with
+    /// [`TypesenseError::Timeout`](crate::TypesenseError::Timeout).
+    ///
+    /// It can be overridden per request with [`Transport::send_with_timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout to establish a connection to a node.
+    ///
+    /// Only the clients made by `new_hyper`, `new_hyper_rustls` and
+    /// `new_hyper_rustls_with_tls` support it. The other clients bring their
+    /// own connector, which has to be configured before being given to the
+    /// builder: a [`TypesenseError::ConfigError`](crate::TypesenseError::ConfigError)
+    /// is returned for them.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> crate::Result<Self> {
+        let with_connect_timeout = self.with_connect_timeout.as_ref().ok_or_else(|| {
+            crate::TypesenseError::ConfigError(
+                "the connect timeout can't be set on a custom client".to_string(),
+            )
+        })?;
+        self.client = with_connect_timeout(connect_timeout);
+        Ok(self)
+    }
+
+    /// Set the [`CircuitBreaker`] failing fast the requests to the
+    /// nodes that keep failing. Without one, every request is sent.
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Add a [`Middleware`] to the chain of middlewares,
+    /// called around every attempt of a request.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middlewares.push(Arc::new(middleware));
+        self
+    }
+
+    /// Set if the bodies of the requests are compressed with gzip,
+    /// which is worth it for large imports over slow links.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn gzip_requests(mut self, gzip_requests: bool) -> Self {
+        self.gzip_requests = gzip_requests;
+        self
+    }
+
+    /// Set if the nodes may compress the bodies of the responses with gzip.
+    ///
+    /// They are only asked for with clients that decode them, see
+    /// [`HttpLowLevel::decodes_gzip`](super::HttpLowLevel::decodes_gzip):
+    /// the [`hyper`](https://docs.rs/hyper) clients and the browsers.
+    /// [`reqwest`](https://docs.rs/reqwest) asks for them on its own
+    /// with its `gzip` feature.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "gzip")))]
+    pub fn gzip_responses(mut self, gzip_responses: bool) -> Self {
+        self.gzip_responses = gzip_responses;
+        self
+    }
+
     /// Make a [`Transport`] struct from the builder.
     pub fn build(self) -> Transport<C> {
         Transport {
             client: self.client,
+            retry_policy: self.retry_policy,
+            timeout: self.timeout,
+            middlewares: self.middlewares,
+            circuits: self
+                .circuit_breaker
+                .map(|breaker| Arc::new(Circuits::new(breaker))),
+            #[cfg(feature = "gzip")]
+            gzip_requests: self.gzip_requests,
+            #[cfg(feature = "gzip")]
+            gzip_responses: self.gzip_responses,
         }
     }
 }
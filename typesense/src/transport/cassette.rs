@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use typesense_codegen::models::ErrorResponse;
+
+use super::HttpLowLevel;
+use crate::client::TYPESENSE_API_KEY_QUERY_PARAM;
+use crate::{ApiError, TypesenseError};
+
+/// A low level HTTP client recording the interactions of another client
+/// to a cassette file, or replaying them without any network access.
+///
+/// The cassette is a [JSON Lines](https://jsonlines.org) file, with one
+/// request and its response per line. Only the method, the path, the query
+/// and the body of the requests are recorded, and the API key is left out
+/// of the query, so it never ends up in a cassette. The bodies are recorded
+/// as text when they are UTF-8, like most Typesense requests and responses,
+/// and in base64 otherwise, like the bodies compressed with gzip.
+///
+/// When replaying, every request is answered with the first recorded
+/// response for the same method, path, query and body, which is then
+/// discarded. Requests without a recorded response fail with
+/// [`TypesenseError::UnrecordedRequest`].
+///
+/// ```no_run
+/// use typesense::transport::{Cassette, TransportBuilder};
+///
+/// # fn main() -> typesense::Result<()> {
+/// let transport = TransportBuilder::new(Cassette::replay("tests/cassettes/search.jsonl")?).build();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Cassette<C = Offline> {
+    mode: Mode<C>,
+}
+
+#[derive(Clone)]
+enum Mode<C> {
+    Record {
+        client: C,
+        file: Arc<Mutex<File>>,
+    },
+    Replay {
+        interactions: Arc<Mutex<Vec<Interaction>>>,
+    },
+}
+
+/// The client of a [`Cassette`] replaying its interactions,
+/// which never sends any request.
+#[derive(Clone, Copy, Debug)]
+pub enum Offline {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+    request: InteractionRequest,
+    response: InteractionResponse,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct InteractionRequest {
+    method: String,
+    uri: String,
+    body: Body,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InteractionResponse {
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: Body,
+}
+
+/// A body, as text if it is UTF-8.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Body {
+    Text(String),
+    Binary { base64: String },
+}
+
+impl Body {
+    fn new(body: &[u8]) -> Self {
+        match std::str::from_utf8(body) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Binary {
+                base64: base64::encode(body),
+            },
+        }
+    }
+
+    fn into_bytes(self) -> crate::Result<Vec<u8>> {
+        match self {
+            Self::Text(text) => Ok(text.into_bytes()),
+            Self::Binary { base64 } => base64::decode(base64).map_err(|e| {
+                TypesenseError::ConfigError(format!("invalid body in cassette: {}", e))
+            }),
+        }
+    }
+}
+
+impl Cassette {
+    /// Replay the interactions recorded in the cassette at `path`.
+    pub fn replay(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| {
+            TypesenseError::ConfigError(format!("cannot read cassette {}: {}", path.display(), e))
+        })?;
+        let interactions = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            mode: Mode::Replay {
+                interactions: Arc::new(Mutex::new(interactions)),
+            },
+        })
+    }
+}
+
+impl<C> Cassette<C> {
+    /// Send the requests with `client`, and record them along with their
+    /// responses to the cassette at `path`, replacing its previous content.
+    pub fn record(client: C, path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|e| {
+            TypesenseError::ConfigError(format!("cannot create cassette {}: {}", path.display(), e))
+        })?;
+
+        Ok(Self {
+            mode: Mode::Record {
+                client,
+                file: Arc::new(Mutex::new(file)),
+            },
+        })
+    }
+}
+
+#[async_trait]
+impl<C: HttpLowLevel> HttpLowLevel for Cassette<C> {
+    async fn send(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        let request = InteractionRequest {
+            method: method.to_string(),
+            uri: path_and_query(uri)?,
+            body: Body::new(&body),
+        };
+
+        match &self.mode {
+            Mode::Record { client, file } => {
+                let result = client.send(method, uri, headers, body).await;
+                let response = match &result {
+                    Ok(response) => InteractionResponse {
+                        status: response.status().as_u16(),
+                        headers: response
+                            .headers()
+                            .iter()
+                            .filter_map(|(name, value)| {
+                                Some((name.to_string(), value.to_str().ok()?.to_string()))
+                            })
+                            .collect(),
+                        body: Body::new(response.body()),
+                    },
+                    Err(error) => match error.api_error() {
+                        Some(error) => InteractionResponse {
+                            status: error.status.as_u16(),
+                            headers: BTreeMap::new(),
+                            body: Body::Text(serde_json::to_string(&ErrorResponse {
+                                message: error.message.clone(),
+                            })?),
+                        },
+                        // Errors without a response are not recorded.
+                        None => return result,
+                    },
+                };
+
+                let mut line = serde_json::to_string(&Interaction { request, response })?;
+                line.push('\n');
+                file.lock().unwrap().write_all(line.as_bytes())?;
+
+                result
+            }
+            Mode::Replay { interactions } => {
+                let response = {
+                    let mut interactions = interactions.lock().unwrap();
+                    let index = interactions
+                        .iter()
+                        .position(|interaction| interaction.request == request)
+                        .ok_or_else(|| {
+                            TypesenseError::UnrecordedRequest(format!(
+                                "{} {}",
+                                request.method, request.uri
+                            ))
+                        })?;
+                    interactions.remove(index).response
+                };
+
+                let status =
+                    http::StatusCode::from_u16(response.status).map_err(http::Error::from)?;
+                let body = response.body.into_bytes()?;
+                if !status.is_success() {
+                    return Err(ApiError::from_response(status, &body).into());
+                }
+
+                let mut builder = http::Response::builder().status(status);
+                for (name, value) in &response.headers {
+                    builder = builder.header(name.as_str(), value.as_str());
+                }
+                Ok(builder.body(body)?)
+            }
+        }
+    }
+
+    fn decodes_gzip(&self) -> bool {
+        match &self.mode {
+            Mode::Record { client, .. } => client.decodes_gzip(),
+            Mode::Replay { .. } => false,
+        }
+    }
+}
+
+#[async_trait]
+impl HttpLowLevel for Offline {
+    async fn send(
+        &self,
+        _method: http::Method,
+        _uri: &str,
+        _headers: http::HeaderMap,
+        _body: Vec<u8>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        match *self {}
+    }
+}
+
+/// The part of `uri` identifying a request on any node, and with any key.
+fn path_and_query(uri: &str) -> crate::Result<String> {
+    let uri = uri.parse::<http::Uri>().map_err(http::Error::from)?;
+
+    let mut path_and_query = uri.path().to_string();
+    let query: Vec<_> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty() && !is_api_key(param))
+        .collect();
+    if !query.is_empty() {
+        path_and_query.push('?');
+        path_and_query.push_str(&query.join("&"));
+    }
+    Ok(path_and_query)
+}
+
+/// Whether a query parameter holds the API key, its name being
+/// compared once decoded.
+fn is_api_key(param: &str) -> bool {
+    serde_urlencoded::from_str::<Vec<(String, String)>>(param)
+        .unwrap_or_default()
+        .iter()
+        .any(|(name, _)| name == TYPESENSE_API_KEY_QUERY_PARAM)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{HeaderMap, Method, StatusCode};
+
+    use super::*;
+    use crate::transport::MockTransport;
+
+    fn cassette_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("typesense-{}-{}.jsonl", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_interactions() {
+        let path = cassette_path("replays_recorded_interactions");
+
+        let mock = MockTransport::new();
+        mock.respond(
+            Method::POST,
+            "/collections",
+            StatusCode::CREATED,
+            r#"{"name":"a"}"#,
+        )
+        .respond(Method::GET, "/collections/b", StatusCode::NOT_FOUND, "");
+        let cassette = Cassette::record(mock.clone(), &path).unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-typesense-api-key", "VerySecretKey".parse().unwrap());
+        let response = cassette
+            .send(
+                Method::POST,
+                "http://a:8108/collections",
+                headers,
+                br#"{"name":"a"}"#.to_vec(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.body(), br#"{"name":"a"}"#);
+        let response = cassette
+            .send(
+                Method::GET,
+                "http://a:8108/collections/b",
+                HeaderMap::new(),
+                Vec::new(),
+            )
+            .await;
+        assert!(matches!(response, Err(TypesenseError::ObjectNotFound(_))));
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(!content.contains("VerySecretKey"));
+
+        let cassette = Cassette::replay(&path).unwrap();
+        let response = cassette
+            .send(
+                Method::GET,
+                "http://b:8108/collections/b",
+                HeaderMap::new(),
+                Vec::new(),
+            )
+            .await;
+        assert!(matches!(response, Err(TypesenseError::ObjectNotFound(_))));
+        let response = cassette
+            .send(
+                Method::POST,
+                "http://b:8108/collections",
+                HeaderMap::new(),
+                br#"{"name":"a"}"#.to_vec(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.body(), br#"{"name":"a"}"#);
+
+        let response = cassette
+            .send(
+                Method::POST,
+                "http://b:8108/collections",
+                HeaderMap::new(),
+                br#"{"name":"a"}"#.to_vec(),
+            )
+            .await;
+        assert!(
+            matches!(response, Err(TypesenseError::UnrecordedRequest(request)) if request == "POST /collections")
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn leaves_api_key_out_of_query() {
+        let path = cassette_path("leaves_api_key_out_of_query");
+
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/collections", StatusCode::OK, "[]");
+        let cassette = Cassette::record(mock, &path).unwrap();
+        let uri = "http://a:8108/collections?x-typesense-api-key=VerySecretKey&limit=1&x%2Dtypesense-api-key=VerySecretKey";
+        cassette
+            .send(Method::GET, uri, HeaderMap::new(), Vec::new())
+            .await
+            .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("VerySecretKey"));
+        assert!(content.contains(r#""uri":"/collections?limit=1""#));
+
+        let cassette = Cassette::replay(&path).unwrap();
+        let uri = "http://b:8108/collections?x-typesense-api-key=OtherKey&limit=1";
+        let response = cassette
+            .send(Method::GET, uri, HeaderMap::new(), Vec::new())
+            .await
+            .unwrap();
+        assert_eq!(response.body(), b"[]");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn replays_binary_bodies() {
+        async fn send<C: HttpLowLevel>(cassette: &Cassette<C>, body: Vec<u8>) -> Vec<u8> {
+            let uri = "http://a:8108/collections";
+            let response = cassette.send(Method::POST, uri, HeaderMap::new(), body);
+            response.await.unwrap().into_body()
+        }
+
+        let path = cassette_path("replays_binary_bodies");
+        let compressed = vec![0x1f, 0x8b, 0x08, 0x00, 0xff];
+
+        let mock = MockTransport::new();
+        mock.respond(
+            Method::POST,
+            "/collections",
+            StatusCode::OK,
+            compressed.clone(),
+        );
+        let cassette = Cassette::record(mock, &path).unwrap();
+        send(&cassette, compressed.clone()).await;
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains(r#""body":{"base64":"H4sIAP8="}"#));
+
+        let cassette = Cassette::replay(&path).unwrap();
+        assert_eq!(send(&cassette, compressed.clone()).await, compressed);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
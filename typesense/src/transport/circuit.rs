@@ -0,0 +1,269 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use instant::Instant;
+
+use crate::TypesenseError;
+
+/// The configuration of the circuit breakers of the [`Transport`](super::Transport),
+/// one for every node the requests are sent to.
+///
+/// A circuit is closed as long as the node is healthy. It opens when, among
+/// the last [`window`](Self::window) requests sent to the node, the ratio of
+/// those that failed because of the node (connection errors, timeouts and
+/// 5xx) reaches the [`failure_ratio`](Self::failure_ratio). While it is open,
+/// the requests to the node fail right away with
+/// [`TypesenseError::CircuitOpen`], and the [`Client`](crate::Client) sends
+/// them to another node.
+///
+/// After the [`cool_down`](Self::cool_down), the circuit is half-open: a
+/// single request is sent to the node, closing the circuit if it succeeds,
+/// or opening it again if it fails.
+///
+/// By default, a circuit opens when half of the last 20 requests failed,
+/// with at least 10 requests sent, and stays open for 30 seconds.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    failure_ratio: f64,
+    window: usize,
+    min_requests: usize,
+    cool_down: Duration,
+}
+
+impl CircuitBreaker {
+    /// Create a circuit breaker with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the ratio of failed requests, between 0 and 1, opening the circuit.
+    pub fn failure_ratio(mut self, failure_ratio: f64) -> Self {
+        self.failure_ratio = failure_ratio.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the number of the last requests the failure ratio is computed on.
+    pub fn window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Set the minimum number of requests sent to a node
+    /// before its circuit can open.
+    pub fn min_requests(mut self, min_requests: usize) -> Self {
+        self.min_requests = min_requests.max(1);
+        self
+    }
+
+    /// Set the time a circuit stays open before a request is sent again to the node.
+    pub fn cool_down(mut self, cool_down: Duration) -> Self {
+        self.cool_down = cool_down;
+        self
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_ratio: 0.5,
+            window: 20,
+            min_requests: 10,
+            cool_down: Duration::from_secs(30),
+        }
+    }
+}
+
+/// The circuits of the nodes, by scheme and authority.
+pub(crate) struct Circuits {
+    breaker: CircuitBreaker,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+#[derive(Default)]
+struct Circuit {
+    state: State,
+    /// Whether the last requests failed, from the oldest one.
+    outcomes: VecDeque<bool>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum State {
+    #[default]
+    Closed,
+    Open {
+        until: Instant,
+    },
+    /// A trial request was sent at `trial_at`. Should it never complete,
+    /// another one is sent once the cool-down elapsed again.
+    HalfOpen {
+        trial_at: Instant,
+    },
+}
+
+impl Circuits {
+    pub(crate) fn new(breaker: CircuitBreaker) -> Self {
+        Self {
+            breaker,
+            circuits: Mutex::default(),
+        }
+    }
+
+    /// The node a request to `uri` is sent to, if its circuit lets the
+    /// request through, otherwise [`TypesenseError::CircuitOpen`].
+    pub(crate) fn check(&self, uri: &str) -> crate::Result<String> {
+        let node = node(uri);
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits.entry(node.clone()).or_default();
+
+        if circuit.allows(Instant::now(), self.breaker.cool_down) {
+            Ok(node)
+        } else {
+            Err(TypesenseError::CircuitOpen(node))
+        }
+    }
+
+    /// Record the outcome of a request let through by [`check`](Self::check).
+    pub(crate) fn record<R>(&self, node: &str, result: &crate::Result<R>) {
+        let failed = matches!(result, Err(e) if e.is_node_failure());
+        let mut circuits = self.circuits.lock().unwrap();
+
+        if let Some(circuit) = circuits.get_mut(node) {
+            circuit.record(failed, Instant::now(), &self.breaker);
+        }
+    }
+}
+
+impl Circuit {
+    fn allows(&mut self, now: Instant, cool_down: Duration) -> bool {
+        match self.state {
+            State::Closed => true,
+            State::Open { until } if now < until => false,
+            State::HalfOpen { trial_at } if now.duration_since(trial_at) < cool_down => false,
+            State::Open { .. } | State::HalfOpen { .. } => {
+                self.state = State::HalfOpen { trial_at: now };
+                true
+            }
+        }
+    }
+
+    fn record(&mut self, failed: bool, now: Instant, breaker: &CircuitBreaker) {
+        match self.state {
+            State::HalfOpen { .. } if failed => self.open(now, breaker),
+            State::HalfOpen { .. } => {
+                self.state = State::Closed;
+                self.outcomes.clear();
+            }
+            State::Closed => {
+                self.outcomes.push_back(failed);
+                if self.outcomes.len() > breaker.window {
+                    self.outcomes.pop_front();
+                }
+
+                let failures = self.outcomes.iter().filter(|failed| **failed).count();
+                let requests = self.outcomes.len();
+                if requests >= breaker.min_requests.min(breaker.window)
+                    && failures > 0
+                    && failures as f64 >= breaker.failure_ratio * requests as f64
+                {
+                    self.open(now, breaker);
+                }
+            }
+            // A request sent before the circuit opened.
+            State::Open { .. } => {}
+        }
+    }
+
+    fn open(&mut self, now: Instant, breaker: &CircuitBreaker) {
+        self.state = State::Open {
+            until: now + breaker.cool_down,
+        };
+        self.outcomes.clear();
+    }
+}
+
+/// The scheme and authority of `uri`, identifying the node it targets.
+fn node(uri: &str) -> String {
+    match uri.parse::<http::Uri>() {
+        Ok(uri) => match (uri.scheme_str(), uri.authority()) {
+            (Some(scheme), Some(authority)) => format!("{}://{}", scheme, authority),
+            _ => uri.to_string(),
+        },
+        Err(_) => uri.to_string(),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_on_failures_and_closes_after_trial() {
+        let breaker = CircuitBreaker::new()
+            .window(4)
+            .min_requests(4)
+            .failure_ratio(0.5)
+            .cool_down(Duration::from_secs(30));
+        let start = Instant::now();
+        let mut circuit = Circuit::default();
+
+        for failed in [false, false, false, true] {
+            assert!(circuit.allows(start, breaker.cool_down));
+            circuit.record(failed, start, &breaker);
+        }
+        assert_eq!(circuit.state, State::Closed);
+
+        circuit.record(true, start, &breaker);
+        assert_eq!(
+            circuit.state,
+            State::Open {
+                until: start + breaker.cool_down
+            }
+        );
+        assert!(!circuit.allows(start, breaker.cool_down));
+
+        // A single trial request is let through after the cool-down.
+        let later = start + breaker.cool_down;
+        assert!(circuit.allows(later, breaker.cool_down));
+        assert!(!circuit.allows(later, breaker.cool_down));
+
+        circuit.record(true, later, &breaker);
+        assert!(!circuit.allows(later, breaker.cool_down));
+
+        let later = later + breaker.cool_down;
+        assert!(circuit.allows(later, breaker.cool_down));
+        circuit.record(false, later, &breaker);
+        assert_eq!(circuit.state, State::Closed);
+        assert!(circuit.allows(later, breaker.cool_down));
+    }
+
+    #[test]
+    fn circuits_are_per_node() {
+        assert_eq!(node("http://a:8108/keys?x=1"), "http://a:8108");
+        assert_eq!(node("https://a/collections"), "https://a");
+    }
+
+    #[tokio::test]
+    async fn transport_fails_fast_while_circuit_is_open() {
+        use http::{Method, StatusCode};
+
+        use crate::transport::{MockTransport, TransportBuilder};
+
+        let mock = MockTransport::new();
+        mock.respond(Method::GET, "/keys", StatusCode::SERVICE_UNAVAILABLE, "");
+        let transport = TransportBuilder::new(mock.clone())
+            .circuit_breaker(CircuitBreaker::new().window(2).min_requests(2))
+            .build();
+        let send = || transport.send(Method::GET, "http://a/keys", Default::default(), Vec::new());
+
+        for _ in 0..2 {
+            let error = send().await.unwrap_err();
+            assert!(matches!(error, TypesenseError::ServiceUnavailable(_)));
+        }
+        let error = send().await.unwrap_err();
+
+        assert!(matches!(&error, TypesenseError::CircuitOpen(node) if node == "http://a"));
+        assert!(error.is_node_failure());
+        assert_eq!(mock.requests().len(), 2);
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{self, Write};
+
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+#[cfg(not(target_arch = "wasm32"))]
+use flate2::write::MultiGzDecoder;
+use flate2::Compression;
+use futures_util::StreamExt;
+
+use super::{BodyStream, MaybeSend};
+
+/// The value of the `Content-Encoding` and `Accept-Encoding` headers.
+pub(crate) const GZIP: &str = "gzip";
+
+/// Whether a body with `headers` is gzipped.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn is_gzip(headers: &http::HeaderMap) -> bool {
+    headers
+        .get(http::header::CONTENT_ENCODING)
+        .is_some_and(|encoding| encoding == GZIP)
+}
+
+/// Whether a request with `headers` accepts a gzipped response.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn accepts_gzip(headers: &http::HeaderMap) -> bool {
+    headers
+        .get_all(http::header::ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|encodings| encodings.to_str().ok())
+        .flat_map(|encodings| encodings.split(','))
+        .any(|encoding| encoding.trim().starts_with(GZIP))
+}
+
+/// Remove the headers describing a body, once it has been decoded.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn remove_encoding(headers: &mut http::HeaderMap) {
+    headers.remove(http::header::CONTENT_ENCODING);
+    headers.remove(http::header::CONTENT_LENGTH);
+}
+
+pub(crate) fn encode(body: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+/// Decode a whole body; the browsers decode the bodies on their own.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn decode(body: &[u8]) -> io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    flate2::read::MultiGzDecoder::new(body).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+pub(crate) fn encode_stream(body: BodyStream) -> BodyStream {
+    transcode(body, GzEncoder::new(Vec::new(), Compression::default()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn decode_stream(body: BodyStream) -> BodyStream {
+    transcode(body, MultiGzDecoder::new(Vec::new()))
+}
+
+/// A gzip encoder or decoder, writing to a buffer.
+trait Coder: Write {
+    fn take(&mut self) -> Vec<u8>;
+    fn finish(self) -> io::Result<Vec<u8>>;
+}
+
+impl Coder for GzEncoder<Vec<u8>> {
+    fn take(&mut self) -> Vec<u8> {
+        std::mem::take(self.get_mut())
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        GzEncoder::finish(self)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Coder for MultiGzDecoder<Vec<u8>> {
+    fn take(&mut self) -> Vec<u8> {
+        std::mem::take(self.get_mut())
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        MultiGzDecoder::finish(self)
+    }
+}
+
+/// Pass every chunk of `body` through `coder`, as they are received.
+fn transcode<C>(body: BodyStream, coder: C) -> BodyStream
+where
+    C: Coder + MaybeSend + 'static,
+{
+    let chunks = futures_util::stream::try_unfold(Some((body, coder)), |state| async move {
+        let (mut body, mut coder) = match state {
+            Some(state) => state,
+            None => return Ok(None),
+        };
+
+        while let Some(chunk) = body.next().await {
+            coder.write_all(&chunk?)?;
+            let output = coder.take();
+            if !output.is_empty() {
+                return Ok(Some((Bytes::from(output), Some((body, coder)))));
+            }
+        }
+
+        Ok(Some((Bytes::from(coder.finish()?), None)))
+    });
+
+    Box::pin(chunks)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    fn chunks(chunks: &[&'static [u8]]) -> BodyStream {
+        let chunks: Vec<_> = chunks.iter().map(|c| Ok(Bytes::from(*c))).collect();
+        Box::pin(futures_util::stream::iter(chunks))
+    }
+
+    async fn collect(body: BodyStream) -> Vec<u8> {
+        let chunks: Vec<Bytes> = body.try_collect().await.unwrap();
+        chunks.concat()
+    }
+
+    #[tokio::test]
+    async fn streams_round_trip() {
+        let documents: &[&[u8]] = &[b"{\"id\":\"1\"}\n", b"{\"id\":\"2\"}\n", b"{\"id\":\"3\"}"];
+
+        let encoded = collect(encode_stream(chunks(documents))).await;
+        assert_eq!(decode(&encoded).unwrap(), documents.concat());
+
+        let encoded = encode(&documents.concat()).unwrap();
+        let (head, tail) = encoded.split_at(encoded.len() / 2);
+        let head = Bytes::copy_from_slice(head);
+        let tail = Bytes::copy_from_slice(tail);
+        let body: BodyStream = Box::pin(futures_util::stream::iter(vec![Ok(head), Ok(tail)]));
+        assert_eq!(collect(decode_stream(body)).await, documents.concat());
+    }
+
+    #[tokio::test]
+    async fn streams_every_member() {
+        let mut encoded = encode(b"{\"id\":\"1\"}\n").unwrap();
+        encoded.extend(encode(b"{\"id\":\"2\"}").unwrap());
+        let (head, tail) = encoded.split_at(encoded.len() / 3);
+        let head = Bytes::copy_from_slice(head);
+        let tail = Bytes::copy_from_slice(tail);
+        let body: BodyStream = Box::pin(futures_util::stream::iter(vec![Ok(head), Ok(tail)]));
+
+        let decoded = b"{\"id\":\"1\"}\n{\"id\":\"2\"}";
+        assert_eq!(decode(&encoded).unwrap(), decoded);
+        assert_eq!(collect(decode_stream(body)).await, decoded);
+    }
+
+    #[tokio::test]
+    async fn transport_compresses_requests() {
+        use crate::transport::{MockTransport, TransportBuilder};
+
+        let mock = MockTransport::new();
+        mock.respond(http::Method::POST, "/import", http::StatusCode::OK, "");
+        let transport = TransportBuilder::new(mock.clone())
+            .gzip_requests(true)
+            .gzip_responses(true)
+            .build();
+
+        transport
+            .send(
+                http::Method::POST,
+                "http://localhost/import",
+                http::HeaderMap::new(),
+                b"{\"id\":\"1\"}".to_vec(),
+            )
+            .await
+            .unwrap();
+
+        let request = &mock.requests()[0];
+        assert!(is_gzip(&request.headers));
+        // The mock would not decode the responses.
+        assert!(!accepts_gzip(&request.headers));
+        assert_eq!(decode(&request.body).unwrap(), b"{\"id\":\"1\"}");
+    }
+
+    #[test]
+    fn reads_accepted_encodings() {
+        let mut headers = http::HeaderMap::new();
+        assert!(!accepts_gzip(&headers));
+
+        headers.insert(
+            http::header::ACCEPT_ENCODING,
+            "br, gzip;q=0.8".parse().unwrap(),
+        );
+        assert!(accepts_gzip(&headers));
+    }
+}
@@ -1,4 +1,10 @@
+use std::pin::Pin;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::future::{self, Either};
+use futures_util::stream::Stream;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub(crate) type HyperClient<C> = hyper::Client<C, hyper::Body>;
@@ -6,15 +12,91 @@ pub(crate) type HyperClient<C> = hyper::Client<C, hyper::Body>;
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
 pub(crate) type HttpsConnector = hyper_tls::HttpsConnector<hyper::client::HttpConnector>;
 
+#[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
+pub(crate) fn https_connector(connect_timeout: Option<Duration>) -> HttpsConnector {
+    let mut http = hyper::client::HttpConnector::new();
+    http.enforce_http(false);
+    http.set_connect_timeout(connect_timeout);
+
+    HttpsConnector::new_with_connector(http)
+}
+
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
 pub(crate) type HyperHttpsClient = HyperClient<HttpsConnector>;
 
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+pub(crate) type RustlsConnector = hyper_rustls::HttpsConnector<hyper::client::HttpConnector>;
+
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+pub(crate) type HyperRustlsClient = HyperClient<RustlsConnector>;
+
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+pub(crate) fn rustls_connector(
+    tls: tokio_rustls::rustls::ClientConfig,
+    connect_timeout: Option<Duration>,
+) -> RustlsConnector {
+    let mut http = hyper::client::HttpConnector::new();
+    http.enforce_http(false);
+    http.set_connect_timeout(connect_timeout);
+
+    hyper_rustls::HttpsConnectorBuilder::new()
+        .with_tls_config(tls)
+        .https_or_http()
+        .enable_http1()
+        .enable_http2()
+        .wrap_connector(http)
+}
+
 #[cfg(target_arch = "wasm32")]
 pub struct WasmClient;
 
+/// A body sent or received chunk by chunk.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BodyStream = Pin<Box<dyn Stream<Item = crate::Result<Bytes>> + Send>>;
+
+/// A body sent or received chunk by chunk.
+#[cfg(target_arch = "wasm32")]
+pub type BodyStream = Pin<Box<dyn Stream<Item = crate::Result<Bytes>>>>;
+
+/// Marker for the types that must be [`Send`] for the futures of the clients
+/// to be [`Send`], that is on every target but `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Marker for the types that must be [`Send`] for the futures of the clients
+/// to be [`Send`], that is on every target but `wasm32`.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSend {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Marker for the types that must be [`Sync`] for the futures of the clients
+/// to be [`Send`], that is on every target but `wasm32`.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait MaybeSync: Sync {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Marker for the types that must be [`Sync`] for the futures of the clients
+/// to be [`Send`], that is on every target but `wasm32`.
+#[cfg(target_arch = "wasm32")]
+pub trait MaybeSync {}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: ?Sized> MaybeSync for T {}
+
 /// A low level HTTP trait.
-#[async_trait(?Send)]
-pub trait HttpLowLevel<M = http::Method, H = http::HeaderMap> {
+///
+/// The futures it returns are [`Send`] on every target but `wasm32`,
+/// where the browser APIs can't be used from another thread.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpLowLevel<M = http::Method, H = http::HeaderMap>: MaybeSend + MaybeSync {
     /// Send a request and receive a response.
     async fn send(
         &self,
@@ -23,10 +105,88 @@ pub trait HttpLowLevel<M = http::Method, H = http::HeaderMap> {
         headers: H,
         body: Vec<u8>,
     ) -> crate::Result<http::Response<Vec<u8>>>;
+
+    /// Send a request and receive a response, failing with
+    /// [`TypesenseError::Timeout`](crate::TypesenseError::Timeout)
+    /// if it takes longer than `timeout`.
+    ///
+    /// The default implementation drops the pending request when
+    /// the timeout elapses.
+    async fn send_with_timeout(
+        &self,
+        method: M,
+        uri: &str,
+        headers: H,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> crate::Result<http::Response<Vec<u8>>>
+    where
+        M: MaybeSend + 'static,
+        H: MaybeSend + 'static,
+    {
+        let send = self.send(method, uri, headers, body);
+        let delay = futures_timer::Delay::new(timeout);
+
+        match future::select(send, delay).await {
+            Either::Left((response, _)) => response,
+            Either::Right(_) => Err(crate::TypesenseError::Timeout),
+        }
+    }
+
+    /// Whether the client decodes the bodies of the responses compressed
+    /// with gzip, which are then asked for when the transport is configured
+    /// to. See [`TransportBuilder::gzip_responses`](super::TransportBuilder::gzip_responses).
+    ///
+    /// The default implementation returns `false`.
+    fn decodes_gzip(&self) -> bool {
+        false
+    }
+
+    /// Send a request whose body is streamed, and receive a response.
+    ///
+    /// The default implementation collects the whole body before sending it.
+    async fn send_body_stream(
+        &self,
+        method: M,
+        uri: &str,
+        headers: H,
+        body: BodyStream,
+    ) -> crate::Result<http::Response<Vec<u8>>>
+    where
+        M: MaybeSend + 'static,
+        H: MaybeSend + 'static,
+    {
+        use futures_util::TryStreamExt;
+
+        let body = body
+            .try_fold(Vec::new(), |mut body, chunk| async move {
+                body.extend_from_slice(&chunk);
+                Ok(body)
+            })
+            .await?;
+
+        self.send(method, uri, headers, body).await
+    }
+}
+
+/// A low level HTTP trait, streaming the body of the response
+/// instead of buffering it.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpLowLevelStream<M = http::Method, H = http::HeaderMap>: HttpLowLevel<M, H> {
+    /// Send a request and receive a response, whose body is
+    /// yielded as it is received.
+    async fn send_streaming(
+        &self,
+        method: M,
+        uri: &str,
+        headers: H,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<BodyStream>>;
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-#[async_trait(?Send)]
+#[async_trait]
 impl<C> HttpLowLevel for HyperClient<C>
 where
     C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
@@ -47,17 +207,120 @@ where
 
         // Building it to a request
         let request = builder.body(body.into())?;
-        // Sending and waiting for a response
+
+        send_hyper(self, request).await
+    }
+
+    fn decodes_gzip(&self) -> bool {
+        true
+    }
+
+    async fn send_body_stream(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: BodyStream,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        // Making a builder
+        let mut builder = http::Request::builder().method(method).uri(uri);
+        // Adding headers
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+
+        // Building it to a request, sending the body as it is produced
+        let request = builder.body(hyper::Body::wrap_stream(body))?;
+
+        send_hyper(self, request).await
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_hyper<C>(
+    client: &HyperClient<C>,
+    request: http::Request<hyper::Body>,
+) -> crate::Result<http::Response<Vec<u8>>>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    // Only the responses asked for are decoded
+    #[cfg(feature = "gzip")]
+    let accepts_gzip = super::gzip::accepts_gzip(request.headers());
+    // Sending and waiting for a response
+    let response = client.request(request).await?;
+
+    #[allow(unused_mut)]
+    let (mut parts, body) = response.into_parts();
+    let body = hyper::body::to_bytes(body).await?;
+
+    #[cfg(feature = "gzip")]
+    let body = if accepts_gzip && super::gzip::is_gzip(&parts.headers) {
+        super::gzip::remove_encoding(&mut parts.headers);
+        super::gzip::decode(&body)?.into()
+    } else {
+        body
+    };
+
+    if parts.status.is_success() {
+        Ok(http::Response::from_parts(parts, body.to_vec()))
+    } else {
+        Err(crate::ApiError::from_response(parts.status, &body).into())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl<C> HttpLowLevelStream for HyperClient<C>
+where
+    C: hyper::client::connect::Connect + Clone + Send + Sync + 'static,
+{
+    async fn send_streaming(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        use futures_util::TryStreamExt;
+
+        // Making a builder
+        let mut builder = http::Request::builder().method(method).uri(uri);
+        // Adding headers
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+
+        // Building it to a request
+        let request = builder.body(body.into())?;
+        // Only the responses asked for are decoded
+        #[cfg(feature = "gzip")]
+        let accepts_gzip = super::gzip::accepts_gzip(request.headers());
+        // Sending and waiting for the head of the response
         let response = self.request(request).await?;
 
-        if response.status().is_success() {
-            let (parts, body) = response.into_parts();
-            let body = hyper::body::to_bytes(body).await?.to_vec();
-            let response = http::Response::from_parts(parts, body);
+        #[allow(unused_mut)]
+        let (mut parts, body) = response.into_parts();
+        let body: BodyStream = Box::pin(body.map_err(Into::into));
+
+        #[cfg(feature = "gzip")]
+        let body = if accepts_gzip && super::gzip::is_gzip(&parts.headers) {
+            super::gzip::remove_encoding(&mut parts.headers);
+            super::gzip::decode_stream(body)
+        } else {
+            body
+        };
 
-            Ok(response)
+        if parts.status.is_success() {
+            Ok(http::Response::from_parts(parts, body))
         } else {
-            return Err(response.status().into());
+            let body = body
+                .try_fold(Vec::new(), |mut body, chunk| async move {
+                    body.extend_from_slice(&chunk);
+                    Ok(body)
+                })
+                .await?;
+            Err(crate::ApiError::from_response(parts.status, &body).into())
         }
     }
 }
@@ -72,88 +335,206 @@ impl HttpLowLevel for WasmClient {
         headers: http::HeaderMap,
         body: Vec<u8>,
     ) -> crate::Result<http::Response<Vec<u8>>> {
-        use js_sys::{Array, ArrayBuffer, Reflect, Uint8Array};
+        fetch(method, uri, headers, body, None).await
+    }
+
+    fn decodes_gzip(&self) -> bool {
+        // The browsers decode the responses on their own.
+        true
+    }
+
+    async fn send_with_timeout(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        // Dropping the future would not stop the browser from fetching,
+        // the request is aborted instead.
+        let controller = web_sys::AbortController::new()?;
+        let send = Box::pin(fetch(method, uri, headers, body, Some(controller.signal())));
+        let delay = futures_timer::Delay::new(timeout);
+
+        match future::select(send, delay).await {
+            Either::Left((response, _)) => response,
+            Either::Right(_) => {
+                controller.abort();
+                Err(crate::TypesenseError::Timeout)
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+impl HttpLowLevelStream for WasmClient {
+    async fn send_streaming(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        use js_sys::{Reflect, Uint8Array};
         use wasm_bindgen::JsCast;
         use wasm_bindgen_futures::JsFuture;
 
-        // Options to configure the request
-        let mut opts = web_sys::RequestInit::new();
-        // Specifying the method
-        opts.method(method.as_str());
-
-        // Pinning the body.
-        let body_pinned = std::pin::Pin::new(body);
-        if body_pinned.len() > 0 {
-            // Creating a JS Typed Array which is a view to into wasm's linear memory.
-            // It could be invalidated if the contents is moved, which is why
-            // we are using `Pin`.
-            // Read more [here](https://docs.rs/js-sys/0.3.51/js_sys/struct.Uint8Array.html#unsafety).
-            let uint_8_array = unsafe { Uint8Array::view(&body_pinned) };
-            opts.body(Some(&uint_8_array));
-        }
+        // Dropping the future or the body would not stop the browser from
+        // fetching, the request is aborted instead, on a timeout as well.
+        let abort = AbortOnDrop(web_sys::AbortController::new()?);
+        let signal = Some(abort.0.signal());
+        let (res, builder) = fetch_head(method, uri, headers, body, signal).await?;
 
-        // Setting the request mode
-        opts.mode(web_sys::RequestMode::Cors);
+        // Reading the response body chunk by chunk
+        let reader: Option<web_sys::ReadableStreamDefaultReader> =
+            res.body().map(|body| body.get_reader().unchecked_into());
+        let chunks =
+            futures_util::stream::try_unfold((reader, abort), |(reader, abort)| async move {
+                let reader = match reader {
+                    Some(reader) => reader,
+                    None => return Ok(None),
+                };
+                let chunk = JsFuture::from(reader.read()).await?;
 
-        // Making a request
-        let request = web_sys::Request::new_with_str_and_init(&uri, &opts)?;
+                let done = Reflect::get(&chunk, &"done".into())?;
+                if done.as_bool().unwrap_or(true) {
+                    return Ok(None);
+                }
 
-        // Adding headers
-        for (name, value) in headers
-            .iter()
-            .map(|(x, y)| (x.as_str(), y.to_str().unwrap()))
-        {
-            request.headers().set(name, value)?;
-        }
+                let value: Uint8Array = Reflect::get(&chunk, &"value".into())?.unchecked_into();
+                Ok(Some((Bytes::from(value.to_vec()), (Some(reader), abort))))
+            });
 
-        let scope = WindowOrWorker::new();
-        // Fetching the request
-        let promise = match scope {
-            WindowOrWorker::Window(window) => window.fetch_with_request(&request),
-            WindowOrWorker::Worker(worker) => worker.fetch_with_request(&request),
-        };
+        Ok(builder.body(Box::pin(chunks) as BodyStream)?)
+    }
+}
 
-        // Converting a JS Promise to a Rust Future and awaiting
-        let res = JsFuture::from(promise).await?;
-        debug_assert!(res.is_instance_of::<web_sys::Response>());
-        let res: web_sys::Response = res.dyn_into().unwrap();
-
-        // Taking the response body
-        let promise_array = res.array_buffer()?;
-        let array = JsFuture::from(promise_array).await?;
-        debug_assert!(array.is_instance_of::<js_sys::ArrayBuffer>());
-        let buf: ArrayBuffer = array.dyn_into().unwrap();
-        // Making a uint8 array
-        let slice = Uint8Array::new(&buf);
-        // Converting it to a Vec
-        let body = slice.to_vec();
+/// Aborts the request of its controller when dropped,
+/// which does nothing once the whole response was received.
+#[cfg(target_arch = "wasm32")]
+struct AbortOnDrop(web_sys::AbortController);
 
-        // Making a builder
-        let mut builder = http::Response::builder().status(res.status());
+#[cfg(target_arch = "wasm32")]
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
 
-        // Adding headers
-        for i in js_sys::try_iter(&res.headers())?.unwrap() {
-            let array: Array = i?.into();
-            let values = array.values();
-
-            let prop = String::from("value").into();
-            let key = Reflect::get(values.next()?.as_ref(), &prop)?
-                .as_string()
-                .unwrap();
-            let value = Reflect::get(values.next()?.as_ref(), &prop)?
-                .as_string()
-                .unwrap();
-            builder = builder.header(&key, &value);
-        }
+#[cfg(target_arch = "wasm32")]
+async fn fetch(
+    method: http::Method,
+    uri: &str,
+    headers: http::HeaderMap,
+    body: Vec<u8>,
+    signal: Option<web_sys::AbortSignal>,
+) -> crate::Result<http::Response<Vec<u8>>> {
+    use js_sys::{ArrayBuffer, Uint8Array};
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
 
-        // Building it to a response
-        let response = builder.body(body)?;
+    let (res, builder) = fetch_head(method, uri, headers, body, signal).await?;
 
-        if response.status().is_success() {
-            Ok(response)
-        } else {
-            return Err(response.status().into());
-        }
+    // Taking the response body
+    let promise_array = res.array_buffer()?;
+    let array = JsFuture::from(promise_array).await?;
+    debug_assert!(array.is_instance_of::<js_sys::ArrayBuffer>());
+    let buf: ArrayBuffer = array.dyn_into().unwrap();
+    // Making a uint8 array
+    let slice = Uint8Array::new(&buf);
+    // Converting it to a Vec
+    let body = slice.to_vec();
+
+    // Building it to a response
+    Ok(builder.body(body)?)
+}
+
+/// Fetch a request, up to the head of the response.
+#[cfg(target_arch = "wasm32")]
+async fn fetch_head(
+    method: http::Method,
+    uri: &str,
+    headers: http::HeaderMap,
+    body: Vec<u8>,
+    signal: Option<web_sys::AbortSignal>,
+) -> crate::Result<(web_sys::Response, http::response::Builder)> {
+    use js_sys::{Array, Reflect, Uint8Array};
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_futures::JsFuture;
+
+    // Options to configure the request
+    let mut opts = web_sys::RequestInit::new();
+    // Specifying the method
+    opts.method(method.as_str());
+
+    // Pinning the body.
+    let body_pinned = std::pin::Pin::new(body);
+    if body_pinned.len() > 0 {
+        // Creating a JS Typed Array which is a view to into wasm's linear memory.
+        // It could be invalidated if the contents is moved, which is why
+        // we are using `Pin`.
+        // Read more [here](https://docs.rs/js-sys/0.3.51/js_sys/struct.Uint8Array.html#unsafety).
+        let uint_8_array = unsafe { Uint8Array::view(&body_pinned) };
+        opts.body(Some(&uint_8_array));
+    }
+
+    // Setting the request mode
+    opts.mode(web_sys::RequestMode::Cors);
+    // Allowing the request to be aborted
+    opts.signal(signal.as_ref());
+
+    // Making a request
+    let request = web_sys::Request::new_with_str_and_init(&uri, &opts)?;
+
+    // Adding headers
+    for (name, value) in headers
+        .iter()
+        .map(|(x, y)| (x.as_str(), y.to_str().unwrap()))
+    {
+        request.headers().set(name, value)?;
+    }
+
+    let scope = WindowOrWorker::new();
+    // Fetching the request
+    let promise = match scope {
+        WindowOrWorker::Window(window) => window.fetch_with_request(&request),
+        WindowOrWorker::Worker(worker) => worker.fetch_with_request(&request),
+    };
+
+    // Converting a JS Promise to a Rust Future and awaiting
+    let res = JsFuture::from(promise).await?;
+    debug_assert!(res.is_instance_of::<web_sys::Response>());
+    let res: web_sys::Response = res.dyn_into().unwrap();
+
+    // Making a builder
+    let mut builder = http::Response::builder().status(res.status());
+
+    // Adding headers
+    for i in js_sys::try_iter(&res.headers())?.unwrap() {
+        let array: Array = i?.into();
+        let values = array.values();
+
+        let prop = String::from("value").into();
+        let key = Reflect::get(values.next()?.as_ref(), &prop)?
+            .as_string()
+            .unwrap();
+        let value = Reflect::get(values.next()?.as_ref(), &prop)?
+            .as_string()
+            .unwrap();
+        builder = builder.header(&key, &value);
+    }
+
+    if res.ok() {
+        Ok((res, builder))
+    } else {
+        let status = http::StatusCode::from_u16(res.status()).map_err(http::Error::from)?;
+        let body = JsFuture::from(res.text()?)
+            .await?
+            .as_string()
+            .unwrap_or_default();
+        Err(crate::ApiError::from_response(status, body.as_bytes()).into())
     }
 }
 
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use crate::TypesenseError;
+
+/// A hook called by the [`Transport`](super::Transport) around every
+/// attempt of a request.
+///
+/// Middlewares can inspect and mutate the outgoing requests, and the
+/// incoming responses or errors. They are called in the order they were
+/// added for the requests, and in the reverse order for the responses and
+/// errors.
+///
+/// When the body of a request or of a response is streamed, the hooks
+/// receive an empty body, and changes made to it are ignored.
+///
+/// ```
+/// use typesense::transport::Middleware;
+///
+/// struct CorrelationId;
+///
+/// impl Middleware for CorrelationId {
+///     fn on_request(&self, request: &mut http::Request<Vec<u8>>) {
+///         request
+///             .headers_mut()
+///             .insert("x-correlation-id", "42".parse().unwrap());
+///     }
+/// }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called before a request is sent.
+    fn on_request(&self, _request: &mut http::Request<Vec<u8>>) {}
+
+    /// Called after a successful response is received.
+    fn on_response(&self, _response: &mut http::Response<Vec<u8>>) {}
+
+    /// Called when a request failed.
+    fn on_error(&self, _error: &mut TypesenseError) {}
+}
+
+/// The ordered list of [`Middleware`]s of a transport.
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares(Vec<Arc<dyn Middleware>>);
+
+impl Middlewares {
+    pub(crate) fn push(&mut self, middleware: Arc<dyn Middleware>) {
+        self.0.push(middleware);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn on_request(&self, request: &mut http::Request<Vec<u8>>) {
+        for middleware in &self.0 {
+            middleware.on_request(request);
+        }
+    }
+
+    pub(crate) fn on_result(&self, result: &mut crate::Result<http::Response<Vec<u8>>>) {
+        for middleware in self.0.iter().rev() {
+            match result {
+                Ok(response) => middleware.on_response(response),
+                Err(error) => middleware.on_error(error),
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::{BodyStream, HttpLowLevel, HttpLowLevelStream};
+use crate::ApiError;
+
+/// An in-memory low level HTTP client, to test code using
+/// the [`Client`](crate::Client) without running a server.
+///
+/// It records every request, and answers with the responses scripted for
+/// their method and path. Requests without a scripted response are answered
+/// with a `404 Not Found`.
+///
+/// The clones of a [`MockTransport`] share their responses and requests,
+/// so a clone can be kept to inspect the requests sent by a client.
+///
+/// ```
+/// use http::{Method, StatusCode};
+/// use typesense::transport::{MockTransport, TransportBuilder};
+/// use typesense::ClientBuilder;
+///
+/// let mock = MockTransport::new();
+/// mock.respond(Method::GET, "/keys", StatusCode::OK, r#"{"keys": []}"#);
+///
+/// let client = ClientBuilder::default()
+///     .transport(TransportBuilder::new(mock.clone()).build())
+///     .host("http://localhost:8108")
+///     .api_key("xyz")
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    responses: Vec<ScriptedResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+struct ScriptedResponse {
+    method: http::Method,
+    path: String,
+    status: http::StatusCode,
+    body: Vec<u8>,
+    once: bool,
+}
+
+/// A request received by a [`MockTransport`].
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    /// The method of the request.
+    pub method: http::Method,
+    /// The full URI of the request, including the host.
+    pub uri: String,
+    /// The headers of the request.
+    pub headers: http::HeaderMap,
+    /// The body of the request.
+    pub body: Vec<u8>,
+}
+
+impl MockTransport {
+    /// Create a mock without any scripted response.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer every request with `method` and `path` with `status` and `body`.
+    ///
+    /// The responses are matched in the order they were scripted.
+    pub fn respond(
+        &self,
+        method: http::Method,
+        path: impl Into<String>,
+        status: http::StatusCode,
+        body: impl Into<Vec<u8>>,
+    ) -> &Self {
+        self.script(method, path.into(), status, body.into(), false)
+    }
+
+    /// Answer the next request with `method` and `path` with `status` and `body`.
+    ///
+    /// The responses are matched in the order they were scripted.
+    pub fn respond_once(
+        &self,
+        method: http::Method,
+        path: impl Into<String>,
+        status: http::StatusCode,
+        body: impl Into<Vec<u8>>,
+    ) -> &Self {
+        self.script(method, path.into(), status, body.into(), true)
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    fn script(
+        &self,
+        method: http::Method,
+        path: String,
+        status: http::StatusCode,
+        body: Vec<u8>,
+        once: bool,
+    ) -> &Self {
+        self.state.lock().unwrap().responses.push(ScriptedResponse {
+            method,
+            path,
+            status,
+            body,
+            once,
+        });
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpLowLevel for MockTransport {
+    async fn send(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        let path = uri
+            .parse::<http::Uri>()
+            .map_err(http::Error::from)?
+            .path()
+            .to_string();
+
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            method: method.clone(),
+            uri: uri.to_string(),
+            headers,
+            body,
+        });
+
+        let index = state
+            .responses
+            .iter()
+            .position(|response| response.method == method && response.path == path);
+        let (status, body) = match index {
+            Some(index) if state.responses[index].once => {
+                let response = state.responses.remove(index);
+                (response.status, response.body)
+            }
+            Some(index) => {
+                let response = &state.responses[index];
+                (response.status, response.body.clone())
+            }
+            None => (http::StatusCode::NOT_FOUND, Vec::new()),
+        };
+
+        if status.is_success() {
+            Ok(http::Response::builder().status(status).body(body)?)
+        } else {
+            Err(ApiError::from_response(status, &body).into())
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpLowLevelStream for MockTransport {
+    async fn send_streaming(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        let response = self.send(method, uri, headers, body).await?;
+
+        Ok(response.map(|body| -> BodyStream {
+            Box::pin(futures_util::stream::once(async { Ok(Bytes::from(body)) }))
+        }))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use http::{HeaderMap, Method, StatusCode};
+
+    use super::*;
+    use crate::TypesenseError;
+
+    #[tokio::test]
+    async fn answers_with_scripted_responses() {
+        let mock = MockTransport::new();
+        mock.respond_once(Method::GET, "/health", StatusCode::SERVICE_UNAVAILABLE, "")
+            .respond(Method::GET, "/health", StatusCode::OK, r#"{"ok":true}"#);
+
+        let response = mock
+            .send(Method::GET, "http://a/health", HeaderMap::new(), Vec::new())
+            .await;
+        assert!(matches!(
+            response,
+            Err(TypesenseError::ServiceUnavailable(_))
+        ));
+
+        for _ in 0..2 {
+            let response = mock
+                .send(
+                    Method::GET,
+                    "http://a/health?x=1",
+                    HeaderMap::new(),
+                    Vec::new(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.body(), br#"{"ok":true}"#);
+        }
+
+        let response = mock
+            .send(
+                Method::POST,
+                "http://a/health",
+                HeaderMap::new(),
+                b"{}".to_vec(),
+            )
+            .await;
+        assert!(matches!(response, Err(TypesenseError::ObjectNotFound(_))));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[3].method, Method::POST);
+        assert_eq!(requests[3].uri, "http://a/health");
+        assert_eq!(requests[3].body, b"{}");
+    }
+}
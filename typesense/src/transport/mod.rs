@@ -1,11 +1,51 @@
 //! The module containing the [`Transport`] struct and
 //! its [`Builder`](TransportBuilder).
 
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::{self, Either};
+
 mod builder;
+#[cfg(not(target_arch = "wasm32"))]
+mod cassette;
+mod circuit;
+#[cfg(feature = "gzip")]
+mod gzip;
 mod http_low_level;
+mod middleware;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+#[cfg(feature = "reqwest")]
+mod reqwest_client;
+mod retry;
+#[cfg(feature = "tower")]
+mod service;
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+mod tls;
 
 pub use builder::TransportBuilder;
-pub use http_low_level::HttpLowLevel;
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub use cassette::{Cassette, Offline};
+pub use circuit::CircuitBreaker;
+pub use http_low_level::{BodyStream, HttpLowLevel, HttpLowLevelStream, MaybeSend, MaybeSync};
+pub use middleware::Middleware;
+#[cfg(any(test, feature = "mock"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub use mock::{MockTransport, RecordedRequest};
+pub(crate) use retry::is_idempotent;
+pub use retry::RetryPolicy;
+#[cfg(feature = "tower")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tower")))]
+pub use service::TowerService;
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "rustls", not(target_arch = "wasm32")))))]
+pub use tls::TlsConfig;
+
+use circuit::Circuits;
+use middleware::Middlewares;
 
 #[cfg(target_arch = "wasm32")]
 pub(crate) use http_low_level::WasmClient;
@@ -13,12 +53,23 @@ pub(crate) use http_low_level::WasmClient;
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
 pub(crate) use http_low_level::HyperHttpsClient;
 
+#[cfg(all(feature = "rustls", not(target_arch = "wasm32")))]
+pub(crate) use http_low_level::HyperRustlsClient;
+
 /// The [`Transport`] struct.
 ///
 /// It handles the low level HTTP client.
 #[derive(Clone)]
 pub struct Transport<C> {
     client: C,
+    retry_policy: Option<RetryPolicy>,
+    timeout: Option<Duration>,
+    middlewares: Middlewares,
+    circuits: Option<Arc<Circuits>>,
+    #[cfg(feature = "gzip")]
+    gzip_requests: bool,
+    #[cfg(feature = "gzip")]
+    gzip_responses: bool,
 }
 
 #[cfg(all(feature = "tokio-rt", not(target_arch = "wasm32")))]
@@ -28,11 +79,21 @@ impl Default for Transport<http_low_level::HyperHttpsClient> {
     }
 }
 
+impl<C> Transport<C> {
+    /// Replace the retry policy, as configured on the client.
+    pub(crate) fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = Some(retry_policy);
+    }
+}
+
 impl<C> Transport<C>
 where
     C: HttpLowLevel,
 {
     /// Send a request and receive a response.
+    ///
+    /// The request is retried according to the [`RetryPolicy`], if any,
+    /// every attempt being limited by the timeout of the transport, if any.
     pub async fn send(
         &self,
         method: http::Method,
@@ -40,7 +101,729 @@ where
         headers: http::HeaderMap,
         body: Vec<u8>,
     ) -> crate::Result<http::Response<Vec<u8>>> {
-        self.client.send(method, uri, headers, body).await
+        self.send_inner(method, uri, headers, body, self.timeout)
+            .await
+    }
+
+    /// Same as [`send`](Self::send), but overriding the timeout
+    /// of the transport for this request.
+    pub async fn send_with_timeout(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        self.send_inner(method, uri, headers, body, Some(timeout))
+            .await
+    }
+
+    async fn send_inner(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        self.with_retries(&method, || {
+            self.send_once(method.clone(), uri, headers.clone(), body.clone(), timeout)
+        })
+        .await
+    }
+
+    /// Send a request whose body is streamed, and receive a response.
+    ///
+    /// As the body can't be replayed, the request is never retried.
+    /// The timeout of the transport still applies.
+    pub async fn send_body_stream(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: BodyStream,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        self.send_body_stream_inner(method, uri, headers, body, self.timeout)
+            .await
+    }
+
+    /// Same as [`send_body_stream`](Self::send_body_stream), but overriding
+    /// the timeout of the transport for this request.
+    pub async fn send_body_stream_with_timeout(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: BodyStream,
+        timeout: Duration,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        self.send_body_stream_inner(method, uri, headers, body, Some(timeout))
+            .await
+    }
+
+    async fn send_body_stream_inner(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: BodyStream,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        if self.middlewares.is_empty() {
+            return self
+                .send_body_stream_raw(method, uri, headers, body, timeout)
+                .await;
+        }
+
+        let mut result = match self.on_request(method, uri, headers, Vec::new()) {
+            Ok(request) => {
+                let (parts, _) = request.into_parts();
+                let uri = parts.uri.to_string();
+
+                self.send_body_stream_raw(parts.method, &uri, parts.headers, body, timeout)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        self.middlewares.on_result(&mut result);
+        result
+    }
+
+    async fn send_once(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        if self.middlewares.is_empty() {
+            return self.send_raw(method, uri, headers, body, timeout).await;
+        }
+
+        let mut result = match self.on_request(method, uri, headers, body) {
+            Ok(request) => {
+                let (parts, body) = request.into_parts();
+                let uri = parts.uri.to_string();
+
+                self.send_raw(parts.method, &uri, parts.headers, body, timeout)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        self.middlewares.on_result(&mut result);
+        result
+    }
+
+    async fn send_raw(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        let (headers, body) = self.encode_request(headers, body)?;
+        let send = async {
+            match timeout {
+                Some(timeout) => {
+                    self.client
+                        .send_with_timeout(method, uri, headers, body, timeout)
+                        .await
+                }
+                None => self.client.send(method, uri, headers, body).await,
+            }
+        };
+
+        self.with_circuit(uri, send).await
+    }
+
+    async fn send_body_stream_raw(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: BodyStream,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        let (headers, body) = self.encode_request_stream(headers, body);
+        let send = self.client.send_body_stream(method, uri, headers, body);
+
+        self.with_circuit(uri, with_timeout(send, timeout)).await
+    }
+}
+
+impl<C> Transport<C>
+where
+    C: HttpLowLevelStream,
+{
+    /// Send a request and receive a response, whose body is
+    /// streamed instead of being buffered.
+    ///
+    /// The [`RetryPolicy`] and the timeout of the transport only
+    /// apply until the head of the response is received.
+    pub async fn send_streaming(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        self.send_streaming_inner(method, uri, headers, body, self.timeout)
+            .await
+    }
+
+    /// Same as [`send_streaming`](Self::send_streaming), but overriding
+    /// the timeout of the transport for this request.
+    pub async fn send_streaming_with_timeout(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Duration,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        self.send_streaming_inner(method, uri, headers, body, Some(timeout))
+            .await
+    }
+
+    async fn send_streaming_inner(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        self.with_retries(&method, || {
+            self.send_streaming_once(method.clone(), uri, headers.clone(), body.clone(), timeout)
+        })
+        .await
+    }
+
+    async fn send_streaming_once(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        if self.middlewares.is_empty() {
+            return self
+                .send_streaming_raw(method, uri, headers, body, timeout)
+                .await;
+        }
+
+        let result = match self.on_request(method, uri, headers, body) {
+            Ok(request) => {
+                let (parts, body) = request.into_parts();
+                let uri = parts.uri.to_string();
+
+                self.send_streaming_raw(parts.method, &uri, parts.headers, body, timeout)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        // The hooks only see the head of the response.
+        let mut body = None;
+        let mut result = result.map(|response| {
+            let (parts, stream) = response.into_parts();
+            body = Some(stream);
+            http::Response::from_parts(parts, Vec::new())
+        });
+
+        self.middlewares.on_result(&mut result);
+        result.map(|response| {
+            let (parts, _) = response.into_parts();
+            http::Response::from_parts(parts, body.take().expect("body of a successful response"))
+        })
+    }
+
+    async fn send_streaming_raw(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+        timeout: Option<Duration>,
+    ) -> crate::Result<http::Response<BodyStream>> {
+        let (headers, body) = self.encode_request(headers, body)?;
+        let send = self.client.send_streaming(method, uri, headers, body);
+
+        self.with_circuit(uri, with_timeout(send, timeout)).await
+    }
+}
+
+impl<C> Transport<C>
+where
+    C: HttpLowLevel,
+{
+    /// Make the request to send, and let the middlewares inspect it.
+    fn on_request(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Request<Vec<u8>>> {
+        let mut builder = http::Request::builder().method(method).uri(uri);
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+
+        let mut request = builder.body(body)?;
+        self.middlewares.on_request(&mut request);
+
+        Ok(request)
+    }
+
+    /// Compress the body of a request and advertise
+    /// the compressed responses accepted, as configured.
+    #[cfg(feature = "gzip")]
+    fn encode_request(
+        &self,
+        mut headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<(http::HeaderMap, Vec<u8>)> {
+        self.accept_encoding(&mut headers);
+        if !self.gzip_requests || body.is_empty() {
+            return Ok((headers, body));
+        }
+
+        let body = gzip::encode(&body)?;
+        headers.insert(
+            http::header::CONTENT_ENCODING,
+            http::HeaderValue::from_static(gzip::GZIP),
+        );
+        headers.remove(http::header::CONTENT_LENGTH);
+
+        Ok((headers, body))
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn encode_request(
+        &self,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<(http::HeaderMap, Vec<u8>)> {
+        Ok((headers, body))
+    }
+
+    /// Same as `encode_request`, for a streamed body.
+    #[cfg(feature = "gzip")]
+    fn encode_request_stream(
+        &self,
+        mut headers: http::HeaderMap,
+        body: BodyStream,
+    ) -> (http::HeaderMap, BodyStream) {
+        self.accept_encoding(&mut headers);
+        if !self.gzip_requests {
+            return (headers, body);
+        }
+
+        headers.insert(
+            http::header::CONTENT_ENCODING,
+            http::HeaderValue::from_static(gzip::GZIP),
+        );
+        headers.remove(http::header::CONTENT_LENGTH);
+
+        (headers, gzip::encode_stream(body))
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn encode_request_stream(
+        &self,
+        headers: http::HeaderMap,
+        body: BodyStream,
+    ) -> (http::HeaderMap, BodyStream) {
+        (headers, body)
+    }
+
+    #[cfg(feature = "gzip")]
+    fn accept_encoding(&self, headers: &mut http::HeaderMap) {
+        if self.gzip_responses && self.client.decodes_gzip() {
+            headers
+                .entry(http::header::ACCEPT_ENCODING)
+                .or_insert(http::HeaderValue::from_static(gzip::GZIP));
+        }
+    }
+}
+
+impl<C> Transport<C> {
+    /// Send a request with `send`, unless the circuit of its node is open,
+    /// recording its outcome. See [`CircuitBreaker`].
+    async fn with_circuit<F, R>(&self, uri: &str, send: F) -> crate::Result<R>
+    where
+        F: Future<Output = crate::Result<R>>,
+    {
+        let circuits = match &self.circuits {
+            Some(circuits) => circuits,
+            None => return send.await,
+        };
+
+        let node = circuits.check(uri)?;
+        let result = send.await;
+        circuits.record(&node, &result);
+        result
+    }
+
+    /// Make attempts until one succeeds or the [`RetryPolicy`], if any, gives up.
+    async fn with_retries<F, Fut, R>(
+        &self,
+        method: &http::Method,
+        mut attempt: F,
+    ) -> crate::Result<R>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = crate::Result<R>>,
+    {
+        let policy = match &self.retry_policy {
+            Some(policy) if policy.allows(method) => policy,
+            _ => return attempt().await,
+        };
+
+        let mut attempts = 1;
+        loop {
+            match attempt().await {
+                // The node is known to be failing, another one is better tried.
+                Err(crate::TypesenseError::CircuitOpen(node)) => {
+                    return Err(crate::TypesenseError::CircuitOpen(node))
+                }
+                Err(e) if policy.should_retry(attempts, &e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(attempt = attempts, error = %e, "retrying request");
+                    futures_timer::Delay::new(policy.backoff(attempts)).await;
+                    attempts += 1;
+                    #[cfg(feature = "tracing")]
+                    tracing::Span::current().record("attempts", attempts);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Fail with [`TypesenseError::Timeout`](crate::TypesenseError::Timeout)
+/// if `future` does not complete within `timeout`.
+async fn with_timeout<F, R>(future: F, timeout: Option<Duration>) -> crate::Result<R>
+where
+    F: Future<Output = crate::Result<R>> + Unpin,
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    match future::select(future, futures_timer::Delay::new(timeout)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(crate::TypesenseError::Timeout),
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod retry_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use http::{HeaderMap, Method, StatusCode};
+
+    use super::*;
+    use crate::TypesenseError;
+
+    /// Answers with a 503 until it has been called `failures` times.
+    struct Flaky {
+        failures: usize,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpLowLevel for Flaky {
+        async fn send(
+            &self,
+            _method: Method,
+            _uri: &str,
+            _headers: HeaderMap,
+            _body: Vec<u8>,
+        ) -> crate::Result<http::Response<Vec<u8>>> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                Err(StatusCode::SERVICE_UNAVAILABLE.into())
+            } else {
+                Ok(http::Response::new(Vec::new()))
+            }
+        }
+    }
+
+    fn transport(failures: usize) -> Transport<Flaky> {
+        TransportBuilder::new(Flaky {
+            failures,
+            calls: AtomicUsize::new(0),
+        })
+        .retry_policy(
+            RetryPolicy::new()
+                .max_attempts(3)
+                .base_backoff(Duration::from_millis(1)),
+        )
+        .build()
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let transport = transport(2);
+
+        let response = transport
+            .send(Method::GET, "/", HeaderMap::new(), Vec::new())
+            .await;
+
+        assert!(response.is_ok());
+        assert_eq!(transport.client.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let transport = transport(5);
+
+        let response = transport
+            .send(Method::GET, "/", HeaderMap::new(), Vec::new())
+            .await;
+
+        assert!(matches!(
+            response,
+            Err(TypesenseError::ServiceUnavailable(_))
+        ));
+        assert_eq!(transport.client.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_non_idempotent_methods() {
+        let transport = transport(5);
+
+        let response = transport
+            .send(Method::POST, "/", HeaderMap::new(), Vec::new())
+            .await;
+
+        assert!(response.is_err());
+        assert_eq!(transport.client.calls.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod timeout_tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use http::{HeaderMap, Method};
+
+    use super::*;
+    use crate::TypesenseError;
+
+    /// Never answers.
+    struct Hung;
+
+    #[async_trait]
+    impl HttpLowLevel for Hung {
+        async fn send(
+            &self,
+            _method: Method,
+            _uri: &str,
+            _headers: HeaderMap,
+            _body: Vec<u8>,
+        ) -> crate::Result<http::Response<Vec<u8>>> {
+            futures_util::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn times_out() {
+        let transport = TransportBuilder::new(Hung)
+            .timeout(Duration::from_millis(10))
+            .build();
+
+        let response = transport
+            .send(Method::GET, "/", HeaderMap::new(), Vec::new())
+            .await;
+        assert!(matches!(response, Err(TypesenseError::Timeout)));
+
+        let response = transport
+            .send_with_timeout(
+                Method::GET,
+                "/",
+                HeaderMap::new(),
+                Vec::new(),
+                Duration::from_millis(1),
+            )
+            .await;
+        assert!(matches!(response, Err(TypesenseError::Timeout)));
+    }
+
+    #[test]
+    fn connect_timeout_needs_built_in_client() {
+        let builder = TransportBuilder::new(Hung).connect_timeout(Duration::from_secs(1));
+        assert!(matches!(builder, Err(TypesenseError::ConfigError(_))));
+
+        #[cfg(feature = "tokio-rt")]
+        assert!(TransportBuilder::new_hyper()
+            .connect_timeout(Duration::from_secs(1))
+            .is_ok());
+        #[cfg(feature = "rustls")]
+        assert!(TransportBuilder::new_hyper_rustls()
+            .connect_timeout(Duration::from_secs(1))
+            .is_ok());
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod body_stream_tests {
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use http::{HeaderMap, Method};
+
+    use super::*;
+
+    /// Answers with the body of the request.
+    struct Echo;
+
+    #[async_trait]
+    impl HttpLowLevel for Echo {
+        async fn send(
+            &self,
+            _method: Method,
+            _uri: &str,
+            _headers: HeaderMap,
+            body: Vec<u8>,
+        ) -> crate::Result<http::Response<Vec<u8>>> {
+            Ok(http::Response::new(body))
+        }
+    }
+
+    #[tokio::test]
+    async fn body_stream_is_collected_by_default() {
+        let transport = TransportBuilder::new(Echo).build();
+        let chunks = vec![
+            Ok(Bytes::from("{\"id\": 1}\n")),
+            Ok(Bytes::from("{\"id\": 2}")),
+        ];
+
+        let response = transport
+            .send_body_stream(
+                Method::POST,
+                "/",
+                HeaderMap::new(),
+                Box::pin(futures_util::stream::iter(chunks)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.body(), b"{\"id\": 1}\n{\"id\": 2}");
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod middleware_tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use http::{HeaderMap, Method, StatusCode};
+
+    use super::*;
+    use crate::TypesenseError;
+
+    /// Answers with the headers of the request, or fails with a 404.
+    struct Echo;
+
+    #[async_trait]
+    impl HttpLowLevel for Echo {
+        async fn send(
+            &self,
+            _method: Method,
+            uri: &str,
+            headers: HeaderMap,
+            _body: Vec<u8>,
+        ) -> crate::Result<http::Response<Vec<u8>>> {
+            if uri == "/missing" {
+                return Err(StatusCode::NOT_FOUND.into());
+            }
+
+            let mut response = http::Response::new(Vec::new());
+            *response.headers_mut() = headers;
+            Ok(response)
+        }
+    }
+
+    /// Records the order in which its hooks are called.
+    struct Recorder {
+        name: &'static str,
+        calls: std::sync::Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Middleware for Recorder {
+        fn on_request(&self, request: &mut http::Request<Vec<u8>>) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} request", self.name));
+            request
+                .headers_mut()
+                .append("x-middleware", self.name.parse().unwrap());
+        }
+
+        fn on_response(&self, _response: &mut http::Response<Vec<u8>>) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} response", self.name));
+        }
+
+        fn on_error(&self, error: &mut TypesenseError) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("{} error", self.name));
+            *error = TypesenseError::ConfigError(self.name.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn middlewares_are_called_in_order() {
+        let calls = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let transport = TransportBuilder::new(Echo)
+            .middleware(Recorder {
+                name: "first",
+                calls: calls.clone(),
+            })
+            .middleware(Recorder {
+                name: "second",
+                calls: calls.clone(),
+            })
+            .build();
+
+        let response = transport
+            .send(Method::GET, "/", HeaderMap::new(), Vec::new())
+            .await
+            .unwrap();
+
+        let names: Vec<_> = response.headers().get_all("x-middleware").iter().collect();
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                "first request",
+                "second request",
+                "second response",
+                "first response"
+            ]
+        );
+
+        let response = transport
+            .send(Method::GET, "/missing", HeaderMap::new(), Vec::new())
+            .await;
+
+        assert!(matches!(response, Err(TypesenseError::ConfigError(name)) if name == "first"));
     }
 }
 
@@ -77,6 +860,29 @@ mod hyper_tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn hyper_streaming() -> crate::Result<()> {
+        use futures_util::TryStreamExt;
+
+        let body = String::from("Test Successful");
+
+        let url = "http://localhost:5000";
+        let mut header = HeaderMap::new();
+        header.insert("Test", "test".parse().unwrap());
+
+        let transport = TransportBuilder::new_hyper().build();
+
+        let response = transport
+            .send_streaming(HttpMethod::GET, url, header, Vec::new())
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let chunks: Vec<_> = response.into_body().try_collect().await?;
+        assert_eq!(chunks.concat(), body.as_bytes());
+
+        Ok(())
+    }
 }
 
 #[cfg(all(test, target_arch = "wasm32"))]
@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+
+use super::HttpLowLevel;
+use crate::ApiError;
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpLowLevel for reqwest::Client {
+    async fn send(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        // Sending and waiting for a response
+        let response = self
+            .request(method, uri)
+            .headers(headers)
+            .body(body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            // Making a builder
+            let mut builder = http::Response::builder().status(response.status());
+            // Adding headers
+            if let Some(h) = builder.headers_mut() {
+                *h = response.headers().clone();
+            }
+
+            let body = response.bytes().await?.to_vec();
+
+            Ok(builder.body(body)?)
+        } else {
+            let status = response.status();
+            let body = response.bytes().await?;
+            Err(ApiError::from_response(status, &body).into())
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod reqwest_tests {
+    use http::Method as HttpMethod;
+    use http::{HeaderMap, StatusCode};
+
+    use crate::transport::TransportBuilder;
+
+    #[tokio::test]
+    async fn reqwest() -> crate::Result<()> {
+        let body = String::from("Test Successful");
+
+        let url = "http://localhost:5000";
+        let mut header = HeaderMap::new();
+        header.insert("Test", "test".parse().unwrap());
+
+        let transport = TransportBuilder::new_reqwest(reqwest::Client::new()).build();
+
+        let response = transport
+            .send(HttpMethod::GET, url, header.clone(), Vec::new())
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body(), body.as_bytes());
+
+        let response = transport
+            .send(HttpMethod::POST, url, header, body.clone().into_bytes())
+            .await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.into_body(), body.as_bytes());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn errors_leave_out_url() {
+        let url = "http://127.0.0.1:1/keys?x-typesense-api-key=VerySecretKey";
+        let error = reqwest::Client::new().get(url).send().await.unwrap_err();
+
+        let error = crate::TypesenseError::from(error);
+        assert!(!error.to_string().contains("VerySecretKey"));
+    }
+}
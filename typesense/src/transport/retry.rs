@@ -0,0 +1,191 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::TypesenseError;
+
+/// The policy deciding if and when a failed request is sent again
+/// by the [`Transport`](super::Transport).
+///
+/// By default a request is attempted at most 3 times, waiting an exponentially
+/// growing backoff (starting at 100ms, capped at 10s) between the attempts.
+/// Only idempotent methods are retried, and only on errors caused by the node
+/// (connection errors, timeouts and 5xx).
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: usize,
+    base_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retry_non_idempotent: bool,
+    retriable: Arc<dyn Fn(&TypesenseError) -> bool + Send + Sync>,
+}
+
+impl RetryPolicy {
+    /// Create a retry policy with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the backoff before the first retry. It doubles after every attempt.
+    pub fn base_backoff(mut self, base_backoff: Duration) -> Self {
+        self.base_backoff = base_backoff;
+        self
+    }
+
+    /// Set the maximum backoff between two attempts.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set if the backoff should be randomized, to spread the retries of
+    /// concurrent requests. When enabled, the actual backoff is picked
+    /// between half and the whole computed backoff.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set if non idempotent methods (like `POST` or `PATCH`) can be retried.
+    pub fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Set the predicate deciding which errors are worth retrying.
+    pub fn retry_on<F>(mut self, retriable: F) -> Self
+    where
+        F: Fn(&TypesenseError) -> bool + Send + Sync + 'static,
+    {
+        self.retriable = Arc::new(retriable);
+        self
+    }
+
+    pub(crate) fn allows(&self, method: &http::Method) -> bool {
+        self.max_attempts > 1 && (self.retry_non_idempotent || is_idempotent(method))
+    }
+
+    /// Whether the request should be attempted again after
+    /// `attempt` attempts ended with `error`.
+    pub(crate) fn should_retry(&self, attempt: usize, error: &TypesenseError) -> bool {
+        attempt < self.max_attempts && (self.retriable)(error)
+    }
+
+    /// The time to wait after the `attempt`th attempt (starting at 1).
+    pub(crate) fn backoff(&self, attempt: usize) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempt.saturating_sub(1) as u32)
+            .unwrap_or(u32::MAX);
+        let backoff = self
+            .base_backoff
+            .checked_mul(factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let half = backoff / 2;
+            half + (backoff - half).mul_f64(fastrand::f64())
+        } else {
+            backoff
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retry_non_idempotent: false,
+            retriable: Arc::new(TypesenseError::is_node_failure),
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_backoff", &self.base_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("retry_non_idempotent", &self.retry_non_idempotent)
+            .finish()
+    }
+}
+
+pub(crate) fn is_idempotent(method: &http::Method) -> bool {
+    use http::Method;
+
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use http::StatusCode;
+
+    use super::*;
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        let policy = RetryPolicy::new()
+            .base_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(1000))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(100), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::new()
+            .base_backoff(Duration::from_millis(100))
+            .jitter(true);
+
+        for _ in 0..100 {
+            let backoff = policy.backoff(2);
+            assert!(backoff >= Duration::from_millis(100));
+            assert!(backoff <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn only_idempotent_methods_are_retried_by_default() {
+        let policy = RetryPolicy::new();
+
+        assert!(policy.allows(&http::Method::GET));
+        assert!(policy.allows(&http::Method::DELETE));
+        assert!(!policy.allows(&http::Method::POST));
+        assert!(!policy.allows(&http::Method::PATCH));
+
+        let policy = policy.retry_non_idempotent(true);
+        assert!(policy.allows(&http::Method::POST));
+    }
+
+    #[test]
+    fn retries_only_retriable_errors() {
+        let policy = RetryPolicy::new()
+            .max_attempts(2)
+            .retry_on(|e| matches!(e, TypesenseError::ServiceUnavailable(_)));
+
+        assert!(policy.should_retry(1, &StatusCode::SERVICE_UNAVAILABLE.into()));
+        assert!(!policy.should_retry(2, &StatusCode::SERVICE_UNAVAILABLE.into()));
+        assert!(!policy.should_retry(1, &StatusCode::NOT_FOUND.into()));
+    }
+}
@@ -0,0 +1,175 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures_util::future::poll_fn;
+use tower_service::Service;
+
+use super::{HttpLowLevel, MaybeSend, MaybeSync};
+use crate::{ApiError, TypesenseError};
+
+/// An adapter to use any [`tower`](https://docs.rs/tower) service
+/// as the low level HTTP client.
+///
+/// The service is cloned for every request, so it should be cheap to clone,
+/// like the services built with a `tower::ServiceBuilder`.
+#[derive(Clone)]
+pub struct TowerService<S>(pub S);
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<S> HttpLowLevel for TowerService<S>
+where
+    S: Service<http::Request<Vec<u8>>, Response = http::Response<Vec<u8>>>
+        + Clone
+        + MaybeSend
+        + MaybeSync,
+    S::Future: MaybeSend,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    async fn send(
+        &self,
+        method: http::Method,
+        uri: &str,
+        headers: http::HeaderMap,
+        body: Vec<u8>,
+    ) -> crate::Result<http::Response<Vec<u8>>> {
+        // Making a builder
+        let mut builder = http::Request::builder().method(method).uri(uri);
+        // Adding headers
+        if let Some(h) = builder.headers_mut() {
+            *h = headers;
+        }
+
+        // Building it to a request
+        let request = builder.body(body)?;
+
+        // Waiting for the service to be ready, and calling it
+        let mut service = self.0.clone();
+        poll_fn(|cx| service.poll_ready(cx))
+            .await
+            .map_err(|e| tower_error(e.into()))?;
+        let response = service
+            .call(request)
+            .await
+            .map_err(|e| tower_error(e.into()))?;
+
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(ApiError::from_response(response.status(), response.body()).into())
+        }
+    }
+}
+
+/// The error of a service, which is a [`TypesenseError::Timeout`]
+/// when it comes from a `tower::timeout` layer.
+fn tower_error(error: Box<dyn Error + Send + Sync>) -> TypesenseError {
+    #[cfg(not(target_arch = "wasm32"))]
+    if error.is::<tower::timeout::error::Elapsed>() {
+        return TypesenseError::Timeout;
+    }
+    TypesenseError::TowerError(error)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+    use std::task::{Context, Poll};
+
+    use http::{HeaderMap, Method, StatusCode};
+
+    use super::*;
+    use crate::transport::TransportBuilder;
+
+    /// Answers with the body of the request.
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<http::Request<Vec<u8>>> for Echo {
+        type Response = http::Response<Vec<u8>>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Vec<u8>>) -> Self::Future {
+            let status = if request.uri() == "/missing" {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::OK
+            };
+            let mut response = http::Response::new(request.into_body());
+            *response.status_mut() = status;
+
+            ready(Ok(response))
+        }
+    }
+
+    #[tokio::test]
+    async fn tower_service() -> crate::Result<()> {
+        let transport = TransportBuilder::new_tower(Echo).build();
+
+        let response = transport
+            .send(Method::POST, "/", HeaderMap::new(), b"body".to_vec())
+            .await?;
+        assert_eq!(response.body(), b"body");
+
+        let response = transport
+            .send(Method::GET, "/missing", HeaderMap::new(), Vec::new())
+            .await;
+        assert!(matches!(response, Err(TypesenseError::ObjectNotFound(_))));
+
+        Ok(())
+    }
+
+    /// Never ready, nor answering the requests.
+    #[derive(Clone)]
+    struct Busy;
+
+    impl Service<http::Request<Vec<u8>>> for Busy {
+        type Response = http::Response<Vec<u8>>;
+        type Error = std::io::Error;
+        type Future = std::future::Pending<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Pending
+        }
+
+        fn call(&mut self, _request: http::Request<Vec<u8>>) -> Self::Future {
+            std::future::pending()
+        }
+    }
+
+    #[tokio::test]
+    async fn layer_errors_are_not_node_failures() {
+        async fn send<S>(service: S) -> TypesenseError
+        where
+            TowerService<S>: HttpLowLevel,
+        {
+            TowerService(service)
+                .send(Method::GET, "/", HeaderMap::new(), Vec::new())
+                .await
+                .unwrap_err()
+        }
+
+        let error = send(tower::ServiceBuilder::new().load_shed().service(Busy)).await;
+        assert!(matches!(error, TypesenseError::TowerError(_)));
+        assert!(!error.is_node_failure());
+
+        let stalled = tower::service_fn(|_| {
+            std::future::pending::<Result<http::Response<Vec<u8>>, std::io::Error>>()
+        });
+        let timeout = tower::timeout::TimeoutLayer::new(std::time::Duration::from_millis(10));
+        let error = send(tower::ServiceBuilder::new().layer(timeout).service(stalled)).await;
+        assert!(matches!(error, TypesenseError::Timeout));
+
+        let reset = tower::service_fn(|_| async {
+            Err::<http::Response<Vec<u8>>, _>(std::io::Error::other("reset"))
+        });
+        let error = send(tower::ServiceBuilder::new().service(reset)).await;
+        assert!(error.is_node_failure());
+    }
+}
@@ -0,0 +1,151 @@
+use tokio_rustls::rustls::{
+    Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore,
+};
+
+use crate::{Result, TypesenseError};
+
+/// TLS configuration of the [`rustls`](https://docs.rs/rustls) based
+/// [`hyper`](https://docs.rs/hyper) client.
+///
+/// By default, the server certificates are verified against the
+/// [webpki roots](https://docs.rs/webpki-roots), and no client
+/// certificate is sent.
+///
+/// ```ignore
+/// let tls = TlsConfig::new()
+///     .add_root_certificates_pem(std::fs::read("ca.pem")?)?
+///     .client_auth_pem(std::fs::read("client.pem")?, std::fs::read("client.key")?)?;
+///
+/// let client = ClientBuilder::new_hyper_rustls_with_tls(tls)?
+///     .host("https://typesense.internal:8108")
+///     .api_key("xyz")
+///     .build()?;
+/// ```
+#[derive(Clone)]
+pub struct TlsConfig {
+    webpki_roots: bool,
+    root_certificates: Vec<Certificate>,
+    client_auth: Option<(Vec<Certificate>, PrivateKey)>,
+}
+
+impl TlsConfig {
+    /// Create a TLS configuration trusting the webpki roots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set if the webpki roots are trusted.
+    pub fn webpki_roots(mut self, webpki_roots: bool) -> Self {
+        self.webpki_roots = webpki_roots;
+        self
+    }
+
+    /// Trust the CA certificates of a PEM bundle.
+    pub fn add_root_certificates_pem(mut self, pem: impl AsRef<[u8]>) -> Result<Self> {
+        let certificates = certificates(pem.as_ref())?;
+        if certificates.is_empty() {
+            return Err(TypesenseError::ConfigError(
+                "no certificate found in the root certificates".to_string(),
+            ));
+        }
+
+        self.root_certificates.extend(certificates);
+        Ok(self)
+    }
+
+    /// Authenticate to the server with a client certificate (mTLS), given the
+    /// PEM encoded certificate chain and private key.
+    pub fn client_auth_pem(
+        mut self,
+        certificate_chain: impl AsRef<[u8]>,
+        private_key: impl AsRef<[u8]>,
+    ) -> Result<Self> {
+        use rustls_pemfile::Item;
+
+        let certificate_chain = certificates(certificate_chain.as_ref())?;
+        if certificate_chain.is_empty() {
+            return Err(TypesenseError::ConfigError(
+                "no certificate found in the client certificate chain".to_string(),
+            ));
+        }
+
+        let private_key = rustls_pemfile::read_all(&mut private_key.as_ref())
+            .map_err(|e| TypesenseError::ConfigError(format!("invalid private key: {}", e)))?
+            .into_iter()
+            .find_map(|item| match item {
+                Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key) => Some(key),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                TypesenseError::ConfigError("no private key found for the client".to_string())
+            })?;
+
+        self.client_auth = Some((certificate_chain, PrivateKey(private_key)));
+        Ok(self)
+    }
+
+    pub(crate) fn into_client_config(self) -> Result<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        if self.webpki_roots {
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+                OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    anchor.subject,
+                    anchor.spki,
+                    anchor.name_constraints,
+                )
+            }));
+        }
+        for certificate in &self.root_certificates {
+            roots.add(certificate).map_err(|e| {
+                TypesenseError::ConfigError(format!("invalid root certificate: {}", e))
+            })?;
+        }
+
+        let builder = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots);
+
+        match self.client_auth {
+            Some((certificate_chain, private_key)) => builder
+                .with_client_auth_cert(certificate_chain, private_key)
+                .map_err(|e| TypesenseError::ConfigError(format!("invalid client auth: {}", e))),
+            None => Ok(builder.with_no_client_auth()),
+        }
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            webpki_roots: true,
+            root_certificates: Vec::new(),
+            client_auth: None,
+        }
+    }
+}
+
+fn certificates(mut pem: &[u8]) -> Result<Vec<Certificate>> {
+    let certificates = rustls_pemfile::certs(&mut pem)
+        .map_err(|e| TypesenseError::ConfigError(format!("invalid certificate: {}", e)))?;
+
+    Ok(certificates.into_iter().map(Certificate).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(TlsConfig::new().into_client_config().is_ok());
+    }
+
+    #[test]
+    fn invalid_pem_is_a_config_error() {
+        let result = TlsConfig::new().add_root_certificates_pem("not a certificate");
+        assert!(matches!(result, Err(TypesenseError::ConfigError(_))));
+
+        let result = TlsConfig::new().client_auth_pem("", "");
+        assert!(matches!(result, Err(TypesenseError::ConfigError(_))));
+    }
+}